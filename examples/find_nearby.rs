@@ -1,4 +1,4 @@
-use dvb::{find_nearby_stops, point::Point};
+use dvb::{Coordinate, find_nearby_stops, point::Point};
 
 #[tokio::main]
 async fn main() {
@@ -14,11 +14,11 @@ async fn main() {
             id,
             city,
             name,
-            coords: (long, lat),
+            coords: Coordinate { northing, easting },
             r#type: typ,
         },
     ) in found.points.iter().enumerate()
     {
-        println!("{ix}. ({id}/{typ:?}) {city} {name} ({long}, {lat})");
+        println!("{ix}. ({id}/{typ:?}) {city} {name} ({northing}, {easting})");
     }
 }
@@ -1,7 +1,4 @@
-use dvb::{
-    find_stops,
-    lines::{Diva, Line},
-};
+use dvb::{Diva, find_stops, lines::Line};
 
 #[tokio::main]
 async fn main() -> dvb::Result<()> {
@@ -19,7 +16,8 @@ async fn main() -> dvb::Result<()> {
     } in &dvb::lines::lines(&origin.id, None).await?.lines
     {
         println!(
-            "Line: {network}-{name} {mot:?} {} directions, {} changes",
+            "Line: {}-{name} {mot:?} {} directions, {} changes",
+            network.as_deref().unwrap_or("?"),
             directions.len(),
             changes.len()
         );
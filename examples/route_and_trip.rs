@@ -27,17 +27,7 @@ async fn main() -> dvb::Result<()> {
 
     let start_time = DvbTime::from(chrono::Local::now());
 
-    let route_params = RouteParams {
-        origin: &origin.id,
-        destination: &destination.id,
-        time: start_time,
-        isarrivaltime: false,
-        shorttermchanges: true,
-        format: "json",
-        via: None,
-        mobility_settings: None,
-        standard_settings: None,
-    };
+    let route_params = RouteParams::new(&origin.id, &destination.id, start_time);
 
     let route_response = route_details(&route_params).await?;
     let routes = &route_response.routes;
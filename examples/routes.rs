@@ -29,17 +29,7 @@ async fn main() -> dvb::Result<()> {
     // Use current time for the route query
     let start_time = DvbTime::from(chrono::Local::now());
 
-    let params = Params {
-        origin: &origin.id,
-        destination: &destination.id,
-        time: start_time,
-        isarrivaltime: false,
-        shorttermchanges: true,
-        format: "json",
-        via: None,
-        mobility_settings: None,
-        standard_settings: None,
-    };
+    let params = Params::new(&origin.id, &destination.id, start_time);
 
     let response = route_details(&params).await?;
     let routes: &Routes = &response;
@@ -61,8 +51,8 @@ async fn main() -> dvb::Result<()> {
         if let Some(interchanges) = route.interchanges {
             println!("  Umstiege: {}", interchanges);
         }
-        if let Some(price) = &route.price {
-            println!("  Preis: {} EUR", price);
+        if let Some(price) = route.price {
+            println!("  Preis: {price}");
         }
         if let Some(partials) = &route.partial_routes {
             for (j, partial) in partials.iter().enumerate() {
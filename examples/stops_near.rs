@@ -0,0 +1,22 @@
+//! Example: Find stops around a WGS84 position (defaults to Dresden Hauptbahnhof)
+
+use dvb::find_stops_near;
+
+#[tokio::main]
+async fn main() -> dvb::Result<()> {
+    let mut args = std::env::args().skip(1).map(|arg| arg.parse::<f64>());
+    let lat = args.next().and_then(Result::ok).unwrap_or(51.0400);
+    let lon = args.next().and_then(Result::ok).unwrap_or(13.7325);
+
+    let nearby = find_stops_near(lat, lon, 500.0).await?;
+
+    println!("Stops within 500m of ({lat}, {lon}):");
+    for stop in &nearby.points {
+        println!(
+            "{:>5.0}m {} ({})",
+            stop.distance, stop.point.name, stop.point.id
+        );
+    }
+
+    Ok(())
+}
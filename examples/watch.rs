@@ -0,0 +1,57 @@
+//! Example: Print changes of the departures at a stop as they happen
+
+use std::{pin::pin, time::Duration};
+
+use dvb::{
+    find_stops,
+    watch::{DepartureEvent, WatchOptions, watch_departures},
+};
+use futures_util::StreamExt;
+
+#[tokio::main]
+async fn main() -> dvb::Result<()> {
+    let query = std::env::args().nth(1).unwrap_or("HauptBahnhof".into());
+    let found = find_stops(&query).await?;
+    let Some(stop) = found.points.first() else {
+        eprintln!("No stop found for '{query}'");
+        return Ok(());
+    };
+
+    println!("Watching departures at {} ({})", stop.name, stop.id);
+    let options = WatchOptions {
+        interval: Duration::from_secs(20),
+        ..Default::default()
+    };
+    let mut events = pin!(watch_departures(stop.id.clone(), options));
+
+    while let Some(event) = events.next().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                eprintln!("poll failed: {error}");
+                continue;
+            }
+        };
+        let dep = event.departure();
+        match &event {
+            DepartureEvent::New(_) => println!("+ {} {}", dep.line_name, dep.direction),
+            DepartureEvent::Gone(_) => println!("- {} {}", dep.line_name, dep.direction),
+            DepartureEvent::DelayChanged { .. } => {
+                println!(
+                    "~ {} {} now {:?}",
+                    dep.line_name, dep.direction, dep.real_time
+                )
+            }
+            DepartureEvent::PlatformChanged { .. } => {
+                println!(
+                    "# {} {} now at {:?}",
+                    dep.line_name, dep.direction, dep.platform
+                )
+            }
+            DepartureEvent::Cancelled(_) => println!("x {} {}", dep.line_name, dep.direction),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Synchronous counterparts of the async API, for use without an async runtime.
+//!
+//! Requires the `blocking` feature.
+//!
+//! # Example
+//! ```no_run
+//! # fn run() -> dvb::Result<()> {
+//! let found = dvb::blocking::find_stops("Hauptbahnhof")?;
+//! if let Some(stop) = found.points.first() {
+//!     let monitor = dvb::blocking::monitor_departures(&stop.id)?;
+//!     println!("{:?}", monitor.departures);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{sync::LazyLock, time::Duration};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    DvbResponse,
+    cache::{CacheStats, ResponseCache},
+    client::{
+        Settings, lines_params, lines_timeout, monitor_params, nearby_stops_params, pois_params,
+        stops_params,
+    },
+    coords::Wgs84,
+    error::Result,
+    lines::{LINES_PATH, Lines},
+    monitor::{self, DepartureMonitor, MONITOR_PATH},
+    point::{self, Found, Nearby, POINT_FINDER_PATH, RawFound, coord_query},
+    route::{self, PREV_NEXT_PATH, PageRequest, ROUTE_PATH, Route, RoutePager, Routes},
+    route_changes::{
+        self, ROUTE_CHANGE_LINES_PATH, ROUTE_CHANGES_PATH, RouteChangeLines, RouteChanges,
+    },
+    time::DvbTime,
+    trip::{self, TRIP_PATH, Trip},
+};
+
+static DEFAULT_CLIENT: LazyLock<DvbClient> = LazyLock::new(DvbClient::new);
+
+/// Blocking client for the VVO WebAPI.
+///
+/// Offers the same configuration and endpoints as [`crate::DvbClient`], but every call blocks
+/// the current thread until the response arrived.
+/// Must not be used from within an async runtime.
+#[derive(Clone, Debug)]
+pub struct DvbClient {
+    http: reqwest::blocking::Client,
+    settings: Settings,
+}
+
+impl Default for DvbClient {
+    fn default() -> Self {
+        Self::with_http_client(reqwest::blocking::Client::new())
+    }
+}
+
+impl DvbClient {
+    /// Creates a client with a default [`reqwest::blocking::Client`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client that sends its requests through the given [`reqwest::blocking::Client`].
+    pub fn with_http_client(http: reqwest::blocking::Client) -> Self {
+        Self {
+            http,
+            settings: Settings::default(),
+        }
+    }
+
+    /// See [`crate::DvbClient::with_base_url`].
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.settings.set_base_url(base_url.into());
+        self
+    }
+
+    /// See [`crate::DvbClient::with_status_check`].
+    pub fn with_status_check(mut self, check_status: bool) -> Self {
+        self.settings.set_check_status(check_status);
+        self
+    }
+
+    /// See [`crate::DvbClient::with_cache`].
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.settings.set_cache(cache);
+        self
+    }
+
+    /// Hit and miss counters of the cache, if one is attached.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.settings.cache_stats()
+    }
+
+    /// The URL all endpoint paths are appended to.
+    pub fn base_url(&self) -> &str {
+        self.settings.base_url()
+    }
+
+    fn post<P, R>(&self, path: &str, params: &P, timeout: Option<Duration>) -> Result<R>
+    where
+        P: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let url = self.settings.url(path);
+        let cache_key = self.settings.cache_key(&url, params)?;
+        if let Some(cached) = self.settings.cached(cache_key.as_deref()) {
+            return cached;
+        }
+
+        let mut request = self.http.post(url).json(params);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response: Value = request.send()?.json()?;
+        self.settings.finish(cache_key, response)
+    }
+
+    /// Search for stops by name, see [`crate::find_stops`].
+    pub fn find_stops(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&stops_params(query))
+    }
+
+    /// Search for nearby and assigned stops, see [`crate::find_nearby_stops`].
+    pub fn find_nearby_stops(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&nearby_stops_params(query))
+    }
+
+    /// Search for points of interest, see [`crate::find_pois`].
+    pub fn find_pois(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&pois_params(query))
+    }
+
+    /// Search for stops around a WGS84 position, see [`crate::find_stops_near`].
+    pub fn find_stops_near(&self, lat: f64, lon: f64, radius: f64) -> Result<DvbResponse<Nearby>> {
+        let origin = Wgs84::new(lat, lon);
+        let query = coord_query(origin);
+        let found = self.point_finder(&nearby_stops_params(&query))?;
+        Ok(found.map(|found| Nearby::new(found, origin, radius)))
+    }
+
+    /// Get upcoming departures for a stop, see [`crate::monitor_departures`].
+    pub fn monitor_departures(&self, stopid: &str) -> Result<DvbResponse<DepartureMonitor>> {
+        self.departure_monitor(monitor_params(stopid))
+    }
+
+    /// Queries the PointFinder, see [`point::point_finder`].
+    pub fn point_finder(&self, params: &point::Params<'_>) -> Result<DvbResponse<Found>> {
+        if params.lenient {
+            let raw: DvbResponse<RawFound> = self.post(POINT_FINDER_PATH, params, None)?;
+            Ok(raw.map(Found::from))
+        } else {
+            self.post(POINT_FINDER_PATH, params, None)
+        }
+    }
+
+    /// Fetches upcoming departures, see [`monitor::departure_monitor`].
+    pub fn departure_monitor(
+        &self,
+        params: monitor::Params<'_>,
+    ) -> Result<DvbResponse<DepartureMonitor>> {
+        self.post(MONITOR_PATH, &params, None)
+    }
+
+    /// Fetches the stops of a trip, see [`trip::trip_details`].
+    pub fn trip_details(&self, params: &trip::Params<'_>) -> Result<DvbResponse<Trip>> {
+        self.post(TRIP_PATH, params, None)
+    }
+
+    /// Queries routes as raw JSON, see [`route::route_details_json`].
+    pub fn route_details_json(&self, params: &route::Params<'_>) -> Result<Value> {
+        self.post(ROUTE_PATH, params, None)
+    }
+
+    /// Queries routes between two stops, see [`route::route_details`].
+    pub fn route_details(&self, params: &route::Params<'_>) -> Result<DvbResponse<Routes>> {
+        self.post(ROUTE_PATH, params, None)
+    }
+
+    /// Fetches the connections before a previous result, see [`route::earlier_routes`].
+    pub fn earlier_routes(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+    ) -> Result<DvbResponse<Routes>> {
+        self.route_page(params, routes, true)
+    }
+
+    /// Fetches the connections after a previous result, see [`route::later_routes`].
+    pub fn later_routes(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+    ) -> Result<DvbResponse<Routes>> {
+        self.route_page(params, routes, false)
+    }
+
+    fn route_page(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+        previous: bool,
+    ) -> Result<DvbResponse<Routes>> {
+        match PageRequest::new(params, routes, previous)? {
+            PageRequest::Session(page) => self.post(PREV_NEXT_PATH, &page, None),
+            PageRequest::Shifted(shifted) => self.route_details(&shifted),
+        }
+    }
+
+    /// Iterates over all connections departing until `deadline`, see [`route::routes_until`].
+    ///
+    /// Every batch is fetched when the previous one is used up, the iterator ends after the first error.
+    pub fn routes_until<'a>(
+        &'a self,
+        params: route::Params<'a>,
+        deadline: DvbTime,
+    ) -> impl Iterator<Item = Result<Route>> + 'a {
+        let mut pager = RoutePager::new(&deadline);
+
+        std::iter::from_fn(move || {
+            if let Some(route) = pager.next_route() {
+                return Some(Ok(route));
+            }
+            if pager.done {
+                return None;
+            }
+
+            let response = match pager.last() {
+                None => self.route_details(&params),
+                Some(last) => self.later_routes(&params, last),
+            };
+            pager.receive(response)
+        })
+    }
+
+    /// Fetches the lines serving a stop, see [`crate::lines::lines`].
+    pub fn lines(&self, stop_id: &str, timeout: Option<u64>) -> Result<DvbResponse<Lines>> {
+        self.post(
+            LINES_PATH,
+            &lines_params(stop_id),
+            Some(lines_timeout(timeout)),
+        )
+    }
+
+    /// Fetches current route changes, see [`route_changes::route_changes`].
+    pub fn route_changes(
+        &self,
+        params: route_changes::Params<'_>,
+    ) -> Result<DvbResponse<RouteChanges>> {
+        self.post(ROUTE_CHANGES_PATH, &params, None)
+    }
+
+    /// Fetches lines affected by route changes, see [`route_changes::route_change_lines`].
+    pub fn route_change_lines(
+        &self,
+        params: route_changes::LinesParams<'_>,
+    ) -> Result<DvbResponse<RouteChangeLines>> {
+        self.post(ROUTE_CHANGE_LINES_PATH, &params, None)
+    }
+}
+
+/// Blocking version of [`crate::find_stops`].
+pub fn find_stops(query: &str) -> Result<DvbResponse<Found>> {
+    DEFAULT_CLIENT.find_stops(query)
+}
+
+/// Blocking version of [`crate::monitor_departures`].
+pub fn monitor_departures(stopid: &str) -> Result<DvbResponse<DepartureMonitor>> {
+    DEFAULT_CLIENT.monitor_departures(stopid)
+}
+
+/// Blocking version of [`trip::trip_details`].
+pub fn trip_details(params: &trip::Params<'_>) -> Result<DvbResponse<Trip>> {
+    DEFAULT_CLIENT.trip_details(params)
+}
+
+/// Blocking version of [`route::route_details`].
+pub fn route_details(params: &route::Params<'_>) -> Result<DvbResponse<Routes>> {
+    DEFAULT_CLIENT.route_details(params)
+}
+
+/// Blocking version of [`crate::lines::lines`].
+pub fn lines(stop_id: &str, timeout: Option<u64>) -> Result<DvbResponse<Lines>> {
+    DEFAULT_CLIENT.lines(stop_id, timeout)
+}
+
+/// Blocking version of [`route_changes::route_changes`].
+pub fn route_changes(params: route_changes::Params<'_>) -> Result<DvbResponse<RouteChanges>> {
+    DEFAULT_CLIENT.route_changes(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::serve_once;
+
+    #[test]
+    fn talks_to_stand_in_server() {
+        let (base_url, server) = serve_once(
+            r#"{"Name":"Hauptbahnhof","Place":"Dresden","Departures":[],"Status":{"Code":"Ok"}}"#,
+        );
+        let client = DvbClient::new().with_base_url(base_url);
+        let monitor = client.monitor_departures("33000028").unwrap();
+
+        assert_eq!(monitor.name.as_deref(), Some("Hauptbahnhof"));
+        assert_eq!(server.join().unwrap(), "POST /dm HTTP/1.1");
+    }
+}
@@ -0,0 +1,210 @@
+//! In-memory response cache that honours the `ExpirationTime` of responses.
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde_json::Value;
+
+use crate::time::DvbTime;
+
+/// Hit and miss counters of a [`ResponseCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests answered from the cache.
+    pub hits: u64,
+    /// Requests that had to be sent to the service.
+    pub misses: u64,
+    /// Responses currently held by the cache.
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    expiration_time: DvbTime,
+    response: Value,
+}
+
+/// Caches raw responses keyed on endpoint and serialized request parameters.
+///
+/// A response is served from the cache until its `ExpirationTime` passes.
+/// Responses without an expiration time are never cached.
+/// Attach it to a client with [`DvbClient::with_cache`](crate::DvbClient::with_cache).
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCache {
+    /// Creates a cache that holds at most `capacity` responses.
+    ///
+    /// When full, expired responses are dropped first, then the one that expires soonest.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+        }
+    }
+
+    /// Maximum number of cached responses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the current hit and miss counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().map(|e| e.len()).unwrap_or_default(),
+        }
+    }
+
+    /// Drops all cached responses, the counters are kept.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    pub(crate) fn key(url: &str, params: &str) -> String {
+        format!("{url}\n{params}")
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Value> {
+        self.get_at(key, &DvbTime::now())
+    }
+
+    fn get_at(&self, key: &str, now: &DvbTime) -> Option<Value> {
+        let mut entries = self.entries.lock().ok()?;
+        let response = match entries.get(key) {
+            Some(entry) if entry.expiration_time.to_datetime() > now.to_datetime() => {
+                Some(entry.response.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if response.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        response
+    }
+
+    pub(crate) fn insert(&self, key: String, response: Value) {
+        self.insert_at(key, response, &DvbTime::now())
+    }
+
+    fn insert_at(&self, key: String, response: Value, now: &DvbTime) {
+        let Some(expiration_time) = response
+            .get("ExpirationTime")
+            .and_then(Value::as_str)
+            .and_then(|raw| DvbTime::from_str(raw).ok())
+        else {
+            return;
+        };
+        if self.capacity == 0 || expiration_time.to_datetime() <= now.to_datetime() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            entries.retain(|_, entry| entry.expiration_time.to_datetime() > now.to_datetime());
+        }
+        if !entries.contains_key(&key)
+            && entries.len() >= self.capacity
+            && let Some(soonest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expiration_time.to_datetime())
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&soonest);
+        }
+
+        entries.insert(
+            key,
+            Entry {
+                expiration_time,
+                response,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(expiration: &str) -> Value {
+        serde_json::json!({ "ExpirationTime": expiration, "Status": { "Code": "Ok" } })
+    }
+
+    fn time(raw: &str) -> DvbTime {
+        DvbTime::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn serves_until_expiration() {
+        let cache = ResponseCache::new(4);
+        let now = time("/Date(1753008400000+0200)/");
+        let later = time("/Date(1753008500000+0200)/");
+
+        cache.insert_at("a".into(), response("/Date(1753008488015+0200)/"), &now);
+
+        assert!(cache.get_at("a", &now).is_some());
+        assert!(cache.get_at("a", &later).is_none());
+        assert!(cache.get_at("b", &now).is_none());
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                entries: 0
+            }
+        );
+    }
+
+    #[test]
+    fn skips_responses_without_expiration() {
+        let cache = ResponseCache::new(4);
+        let now = time("/Date(1753008400000+0200)/");
+
+        cache.insert_at("a".into(), serde_json::json!({}), &now);
+        cache.insert_at("b".into(), response("/Date(1753008300000+0200)/"), &now);
+
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn evicts_soonest_expiring() {
+        let cache = ResponseCache::new(2);
+        let now = time("/Date(1753008400000+0200)/");
+
+        cache.insert_at("a".into(), response("/Date(1753008600000+0200)/"), &now);
+        cache.insert_at("b".into(), response("/Date(1753008500000+0200)/"), &now);
+        cache.insert_at("c".into(), response("/Date(1753008700000+0200)/"), &now);
+
+        assert_eq!(cache.stats().entries, 2);
+        assert!(cache.get_at("a", &now).is_some());
+        assert!(cache.get_at("b", &now).is_none());
+        assert!(cache.get_at("c", &now).is_some());
+    }
+}
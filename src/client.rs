@@ -0,0 +1,613 @@
+//! A reusable client for all VVO WebAPI endpoints.
+
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use futures_util::{Stream, future::try_join_all, stream};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    DvbResponse,
+    cache::{CacheStats, ResponseCache},
+    common::Status,
+    coords::Wgs84,
+    error::Result,
+    lines::{LINES_PATH, Lines},
+    monitor::{self, AggregatedDeparture, DepartureMonitor, MONITOR_PATH},
+    point::{self, Found, Nearby, POINT_FINDER_PATH, RawFound, coord_query},
+    route::{self, PREV_NEXT_PATH, PageRequest, ROUTE_PATH, Route, RoutePager, Routes},
+    route_changes::{
+        self, ROUTE_CHANGE_LINES_PATH, ROUTE_CHANGES_PATH, RouteChangeLines, RouteChanges,
+    },
+    time::DvbTime,
+    trip::{self, TRIP_PATH, Trip},
+    watch::{self, DepartureEvent, WatchOptions},
+};
+
+/// The public VVO WebAPI.
+pub const DEFAULT_BASE_URL: &str = "https://webapi.vvo-online.de";
+
+static DEFAULT_CLIENT: LazyLock<DvbClient> = LazyLock::new(DvbClient::new);
+
+/// The client used by the free endpoint functions such as [`monitor::departure_monitor`].
+pub(crate) fn default_client() -> &'static DvbClient {
+    &DEFAULT_CLIENT
+}
+
+/// Client for the VVO WebAPI.
+///
+/// Owns a [`reqwest::Client`], so connections are pooled across requests.
+/// Cloning a `DvbClient` is cheap and shares the underlying connection pool.
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> dvb::Result<()> {
+/// let client = dvb::DvbClient::new();
+/// let found = client.find_stops("Hauptbahnhof").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DvbClient {
+    http: reqwest::Client,
+    settings: Settings,
+}
+
+/// Configuration shared by the async and the blocking client.
+#[derive(Clone, Debug)]
+pub(crate) struct Settings {
+    base_url: String,
+    check_status: bool,
+    cache: Option<Arc<ResponseCache>>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.into(),
+            check_status: true,
+            cache: None,
+        }
+    }
+}
+
+impl Settings {
+    pub(crate) fn set_base_url(&mut self, base_url: String) {
+        self.base_url = base_url.trim_end_matches('/').into();
+    }
+
+    pub(crate) fn set_check_status(&mut self, check_status: bool) {
+        self.check_status = check_status;
+    }
+
+    pub(crate) fn set_cache(&mut self, cache: ResponseCache) {
+        self.cache = Some(Arc::new(cache));
+    }
+
+    pub(crate) fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    pub(crate) fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Returns the cache key for a request, or `None` if no cache is attached.
+    pub(crate) fn cache_key<P: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        params: &P,
+    ) -> Result<Option<String>> {
+        match self.cache {
+            Some(_) => Ok(Some(ResponseCache::key(
+                url,
+                &serde_json::to_string(params)?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Looks up a still valid response in the cache.
+    pub(crate) fn cached<R: DeserializeOwned>(&self, key: Option<&str>) -> Option<Result<R>> {
+        let response = self.cache.as_ref()?.get(key?)?;
+        Some(self.decode(response))
+    }
+
+    /// Decodes a freshly fetched response and caches it if it decoded successfully.
+    pub(crate) fn finish<R: DeserializeOwned>(
+        &self,
+        key: Option<String>,
+        response: Value,
+    ) -> Result<R> {
+        match (&self.cache, key) {
+            (Some(cache), Some(key)) => {
+                let decoded = self.decode(response.clone())?;
+                cache.insert(key, response);
+                Ok(decoded)
+            }
+            _ => self.decode(response),
+        }
+    }
+
+    fn decode<R: DeserializeOwned>(&self, response: Value) -> Result<R> {
+        if self.check_status
+            && let Some(status) = response.get("Status")
+        {
+            Status::deserialize(status)?.error_for_status()?;
+        }
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
+pub(crate) fn stops_params(query: &str) -> point::Params<'_> {
+    point::Params {
+        query,
+        stops_only: true,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn nearby_stops_params(query: &str) -> point::Params<'_> {
+    point::Params {
+        query,
+        stops_only: false,
+        assigedstops: true,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn pois_params(query: &str) -> point::Params<'_> {
+    point::Params {
+        query,
+        stops_only: false,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn monitor_params(stopid: &str) -> monitor::Params<'_> {
+    monitor::Params {
+        stopid,
+        mot: None,
+        limit: Some(15),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn lines_params(stop_id: &str) -> Value {
+    serde_json::json!({ "stopid": stop_id, "format": "json" })
+}
+
+pub(crate) fn lines_timeout(timeout: Option<u64>) -> Duration {
+    Duration::from_millis(timeout.unwrap_or(15000))
+}
+
+impl Default for DvbClient {
+    fn default() -> Self {
+        Self::with_http_client(reqwest::Client::new())
+    }
+}
+
+impl DvbClient {
+    /// Creates a client with a default [`reqwest::Client`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client that sends its requests through the given [`reqwest::Client`].
+    ///
+    /// Use this to configure timeouts, proxies, user agents and the like.
+    pub fn with_http_client(http: reqwest::Client) -> Self {
+        Self {
+            http,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Sends all requests to `base_url` instead of [`DEFAULT_BASE_URL`].
+    ///
+    /// Endpoint paths such as `/dm` or `/tr/trips` are appended to it,
+    /// which makes it possible to talk to a local mock server or a caching proxy.
+    ///
+    /// # Example
+    /// ```
+    /// let client = dvb::DvbClient::new().with_base_url("http://localhost:8080/vvo/");
+    /// assert_eq!(client.base_url(), "http://localhost:8080/vvo");
+    /// ```
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.settings.set_base_url(base_url.into());
+        self
+    }
+
+    /// Controls whether responses with a non-`Ok` [`Status`](crate::Status) are turned into
+    /// [`Error::Status`](crate::error::Error::Status). Enabled by default.
+    ///
+    /// Disable it to receive the raw response envelope regardless of its status,
+    /// and check it later with [`DvbResponse::error_for_status`] if needed.
+    pub fn with_status_check(mut self, check_status: bool) -> Self {
+        self.settings.set_check_status(check_status);
+        self
+    }
+
+    /// Serves repeated requests from `cache` until the cached responses expire.
+    ///
+    /// Clones of this client share the same cache.
+    ///
+    /// # Example
+    /// ```
+    /// use dvb::{DvbClient, cache::ResponseCache};
+    ///
+    /// let client = DvbClient::new().with_cache(ResponseCache::new(256));
+    /// assert_eq!(client.cache_stats().unwrap().hits, 0);
+    /// ```
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.settings.set_cache(cache);
+        self
+    }
+
+    /// Hit and miss counters of the cache, if one is attached.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.settings.cache_stats()
+    }
+
+    /// The URL all endpoint paths are appended to.
+    pub fn base_url(&self) -> &str {
+        self.settings.base_url()
+    }
+
+    async fn post<P, R>(&self, path: &str, params: &P, timeout: Option<Duration>) -> Result<R>
+    where
+        P: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let url = self.settings.url(path);
+        let cache_key = self.settings.cache_key(&url, params)?;
+        if let Some(cached) = self.settings.cached(cache_key.as_deref()) {
+            return cached;
+        }
+
+        let mut request = self.http.post(url).json(params);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response: Value = request.send().await?.json().await?;
+        self.settings.finish(cache_key, response)
+    }
+
+    /// Search for stops by name, see [`crate::find_stops`].
+    pub async fn find_stops(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&stops_params(query)).await
+    }
+
+    /// Search for nearby and assigned stops, see [`crate::find_nearby_stops`].
+    pub async fn find_nearby_stops(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&nearby_stops_params(query)).await
+    }
+
+    /// Search for points of interest, see [`crate::find_pois`].
+    pub async fn find_pois(&self, query: &str) -> Result<DvbResponse<Found>> {
+        self.point_finder(&pois_params(query)).await
+    }
+
+    /// Search for stops around a WGS84 position, see [`crate::find_stops_near`].
+    pub async fn find_stops_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius: f64,
+    ) -> Result<DvbResponse<Nearby>> {
+        let origin = Wgs84::new(lat, lon);
+        let query = coord_query(origin);
+        let found = self.point_finder(&nearby_stops_params(&query)).await?;
+        Ok(found.map(|found| Nearby::new(found, origin, radius)))
+    }
+
+    /// Get upcoming departures for a stop, see [`crate::monitor_departures`].
+    pub async fn monitor_departures(&self, stopid: &str) -> Result<DvbResponse<DepartureMonitor>> {
+        self.departure_monitor(monitor_params(stopid)).await
+    }
+
+    /// Polls the departures of a stop and yields what changed, see [`watch::watch_departures`].
+    pub fn watch_departures(
+        &self,
+        stopid: impl Into<String>,
+        options: WatchOptions,
+    ) -> impl Stream<Item = Result<DepartureEvent>> + Send + 'static {
+        watch::watch(self.clone(), stopid.into(), options)
+    }
+
+    /// Queries the PointFinder, see [`point::point_finder`].
+    pub async fn point_finder(&self, params: &point::Params<'_>) -> Result<DvbResponse<Found>> {
+        if params.lenient {
+            let raw: DvbResponse<RawFound> = self.post(POINT_FINDER_PATH, params, None).await?;
+            Ok(raw.map(Found::from))
+        } else {
+            self.post(POINT_FINDER_PATH, params, None).await
+        }
+    }
+
+    /// Fetches upcoming departures, see [`monitor::departure_monitor`].
+    pub async fn departure_monitor(
+        &self,
+        params: monitor::Params<'_>,
+    ) -> Result<DvbResponse<DepartureMonitor>> {
+        self.post(MONITOR_PATH, &params, None).await
+    }
+
+    /// Fetches and merges the departures of several stops, see [`monitor::aggregated_departure_monitor`].
+    ///
+    /// Fails as a whole if any of the stops fails.
+    pub async fn aggregated_departure_monitor<'a>(
+        &self,
+        stopids: &[&'a str],
+        params: monitor::Params<'a>,
+    ) -> Result<Vec<AggregatedDeparture>> {
+        let monitors = try_join_all(stopids.iter().map(|&stopid| {
+            self.departure_monitor(monitor::Params {
+                stopid,
+                ..params.clone()
+            })
+        }))
+        .await?;
+
+        Ok(monitor::aggregate(
+            stopids
+                .iter()
+                .copied()
+                .zip(monitors.into_iter().map(DvbResponse::into_inner)),
+        ))
+    }
+
+    /// Fetches the stops of a trip, see [`trip::trip_details`].
+    pub async fn trip_details(&self, params: &trip::Params<'_>) -> Result<DvbResponse<Trip>> {
+        self.post(TRIP_PATH, params, None).await
+    }
+
+    /// Queries routes as raw JSON, see [`route::route_details_json`].
+    pub async fn route_details_json(&self, params: &route::Params<'_>) -> Result<Value> {
+        self.post(ROUTE_PATH, params, None).await
+    }
+
+    /// Queries routes between two stops, see [`route::route_details`].
+    pub async fn route_details(&self, params: &route::Params<'_>) -> Result<DvbResponse<Routes>> {
+        self.post(ROUTE_PATH, params, None).await
+    }
+
+    /// Fetches the connections before a previous result, see [`route::earlier_routes`].
+    pub async fn earlier_routes(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+    ) -> Result<DvbResponse<Routes>> {
+        self.route_page(params, routes, true).await
+    }
+
+    /// Fetches the connections after a previous result, see [`route::later_routes`].
+    pub async fn later_routes(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+    ) -> Result<DvbResponse<Routes>> {
+        self.route_page(params, routes, false).await
+    }
+
+    async fn route_page(
+        &self,
+        params: &route::Params<'_>,
+        routes: &Routes,
+        previous: bool,
+    ) -> Result<DvbResponse<Routes>> {
+        match PageRequest::new(params, routes, previous)? {
+            PageRequest::Session(page) => self.post(PREV_NEXT_PATH, &page, None).await,
+            PageRequest::Shifted(shifted) => self.route_details(&shifted).await,
+        }
+    }
+
+    /// Yields all connections departing until `deadline`, see [`route::routes_until`].
+    pub fn routes_until<'a>(
+        &'a self,
+        params: route::Params<'a>,
+        deadline: DvbTime,
+    ) -> impl Stream<Item = Result<Route>> + 'a {
+        stream::unfold(
+            (params, RoutePager::new(&deadline)),
+            move |(params, mut pager)| async move {
+                if let Some(route) = pager.next_route() {
+                    return Some((Ok(route), (params, pager)));
+                }
+                if pager.done {
+                    return None;
+                }
+
+                let response = match pager.last() {
+                    None => self.route_details(&params).await,
+                    Some(last) => self.later_routes(&params, last).await,
+                };
+                let route = pager.receive(response)?;
+                Some((route, (params, pager)))
+            },
+        )
+    }
+
+    /// Fetches the lines serving a stop, see [`crate::lines::lines`].
+    pub async fn lines(&self, stop_id: &str, timeout: Option<u64>) -> Result<DvbResponse<Lines>> {
+        self.post(
+            LINES_PATH,
+            &lines_params(stop_id),
+            Some(lines_timeout(timeout)),
+        )
+        .await
+    }
+
+    /// Fetches current route changes, see [`route_changes::route_changes`].
+    pub async fn route_changes(
+        &self,
+        params: route_changes::Params<'_>,
+    ) -> Result<DvbResponse<RouteChanges>> {
+        self.post(ROUTE_CHANGES_PATH, &params, None).await
+    }
+
+    /// Fetches lines affected by route changes, see [`route_changes::route_change_lines`].
+    pub async fn route_change_lines(
+        &self,
+        params: route_changes::LinesParams<'_>,
+    ) -> Result<DvbResponse<RouteChangeLines>> {
+        self.post(ROUTE_CHANGE_LINES_PATH, &params, None).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+    use crate::{DvbTime, StatusCode, error::Error};
+
+    /// Serves `body` as JSON to a single request and returns the base URL and the received request line.
+    pub(crate) fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            let request = String::from_utf8_lossy(&request);
+            request.lines().next().unwrap_or_default().to_string()
+        });
+        (base_url, handle)
+    }
+
+    #[test]
+    fn default_base_url() {
+        let client = DvbClient::new();
+        assert_eq!(
+            client.settings.url(MONITOR_PATH),
+            "https://webapi.vvo-online.de/dm"
+        );
+    }
+
+    #[test]
+    fn custom_base_url() {
+        let client = DvbClient::new().with_base_url("http://localhost:8080/proxy/");
+        assert_eq!(
+            client.settings.url(TRIP_PATH),
+            "http://localhost:8080/proxy/dm/trip"
+        );
+    }
+
+    #[tokio::test]
+    async fn talks_to_stand_in_server() {
+        let (base_url, server) = serve_once(
+            r#"{"Name":"Hauptbahnhof","Place":"Dresden","Departures":[],"Status":{"Code":"Ok"}}"#,
+        );
+        let client = DvbClient::new().with_base_url(base_url);
+        let monitor = client.monitor_departures("33000028").await.unwrap();
+
+        assert_eq!(monitor.name.as_deref(), Some("Hauptbahnhof"));
+        assert_eq!(server.join().unwrap(), "POST /dm HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn cached_response_is_reused() {
+        let expiration = DvbTime::in_n_minutes(5);
+        let body = format!(
+            r#"{{"Name":"Hauptbahnhof","ExpirationTime":"{expiration}","Status":{{"Code":"Ok"}}}}"#
+        );
+        let (base_url, server) = serve_once(body.leak());
+        let client = DvbClient::new()
+            .with_base_url(base_url)
+            .with_cache(ResponseCache::new(8));
+
+        client.monitor_departures("33000028").await.unwrap();
+        server.join().unwrap();
+        // the stand-in server is gone, so this can only be answered from the cache
+        let monitor = client.monitor_departures("33000028").await.unwrap();
+
+        assert_eq!(monitor.name.as_deref(), Some("Hauptbahnhof"));
+        assert_eq!(
+            client.cache_stats(),
+            Some(CacheStats {
+                hits: 1,
+                misses: 1,
+                entries: 1
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn later_routes_use_session() {
+        let (base_url, server) =
+            serve_once(r#"{"Routes":[],"SessionId":"2","Status":{"Code":"Ok"}}"#);
+        let client = DvbClient::new().with_base_url(base_url);
+        let params = route::Params::new("33000028", "33000016", DvbTime::now());
+        let first = Routes {
+            routes: Vec::new(),
+            session_id: Some("1".into()),
+        };
+
+        let later = client.later_routes(&params, &first).await.unwrap();
+        assert_eq!(later.session_id.as_deref(), Some("2"));
+        assert_eq!(server.join().unwrap(), "POST /tr/prevnext HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn paging_needs_a_reference() {
+        let client = DvbClient::new().with_base_url("http://127.0.0.1:9");
+        let params = route::Params::new("33000028", "33000016", DvbTime::now());
+        let empty = Routes {
+            routes: Vec::new(),
+            session_id: None,
+        };
+
+        let result = client.earlier_routes(&params, &empty).await;
+        assert!(matches!(result, Err(Error::NoPagingReference)));
+    }
+
+    const VALIDATION_ERROR: &str =
+        r#"{"Status":{"Code":"ValidationError","Message":"stopid is invalid"}}"#;
+
+    #[tokio::test]
+    async fn status_becomes_error() {
+        let (base_url, _) = serve_once(VALIDATION_ERROR);
+        let client = DvbClient::new().with_base_url(base_url);
+
+        match client.monitor_departures("nope").await {
+            Err(Error::Status { code, message }) => {
+                assert_eq!(code, StatusCode::ValidationError);
+                assert_eq!(message.as_deref(), Some("stopid is invalid"));
+            }
+            other => panic!("expected status error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn status_check_opt_out() {
+        let (base_url, _) = serve_once(VALIDATION_ERROR);
+        let client = DvbClient::new()
+            .with_base_url(base_url)
+            .with_status_check(false);
+
+        let monitor = client.monitor_departures("nope").await.unwrap();
+        assert_eq!(monitor.status.code, StatusCode::ValidationError);
+        assert!(monitor.error_for_status().is_err());
+    }
+}
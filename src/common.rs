@@ -5,7 +5,12 @@ use std::{fmt::Debug, ops::Deref};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+use crate::{
+    error::{Error, Result},
+    time::DvbTime,
+};
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum ArrivalState {
     Delayed,
@@ -14,7 +19,7 @@ pub enum ArrivalState {
     Cancelled,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum Mot {
     Tram,
@@ -47,9 +52,58 @@ pub enum Mot {
     MobilityEscalatorDown,
     MobilityRampUp,
     MobilityRampDown,
+    /// A mode this crate doesn't know yet.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+/// Kind of a [`Platform`].
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum PlatformType {
+    /// A platform at a tram or bus stop.
+    Platform,
+    /// A track at a train station.
+    Railtrack,
+    /// Not reported, or a value this crate doesn't know yet.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// The platform or track a vehicle departs from or arrives at.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Platform {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub r#type: PlatformType,
+}
+
+/// Line identifier in the DIVA timetable system.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Diva {
+    pub number: Option<String>,
+    pub network: Option<String>,
+}
+
+/// How crowded a vehicle is expected to be.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Occupancy {
+    ManySeats,
+    FewSeats,
+    StandingOnly,
+    Full,
+    /// No forecast available, or a value this crate doesn't know yet.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum StatusCode {
     Ok,
@@ -63,16 +117,35 @@ pub enum StatusCode {
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Status {
-    code: StatusCode,
-    message: Option<String>,
+    pub code: StatusCode,
+    pub message: Option<String>,
+}
+
+impl Status {
+    /// Returns `true` if the service reported [`StatusCode::Ok`].
+    pub fn is_ok(&self) -> bool {
+        self.code == StatusCode::Ok
+    }
+
+    /// Turns a non-`Ok` status into an [`Error::Status`].
+    pub fn error_for_status(&self) -> Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(Error::Status {
+                code: self.code,
+                message: self.message.clone(),
+            })
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct DvbResponse<T: Debug> {
     pub status: Status,
-    // TODO: parse this
-    pub expiration_time: Option<String>,
+    /// Time until which the service considers this response up to date.
+    pub expiration_time: Option<DvbTime>,
 
     #[serde(flatten)]
     content: T,
@@ -96,4 +169,114 @@ impl<T: Debug> DvbResponse<T> {
     pub fn into_inner(self) -> T {
         self.content
     }
+
+    /// Transforms the content while keeping status and expiration time.
+    pub fn map<U: Debug>(self, f: impl FnOnce(T) -> U) -> DvbResponse<U> {
+        DvbResponse {
+            status: self.status,
+            expiration_time: self.expiration_time,
+            content: f(self.content),
+        }
+    }
+
+    /// Returns `true` if the response has passed its expiration time.
+    ///
+    /// Responses without an expiration time are always considered expired.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(&DvbTime::now())
+    }
+
+    /// Returns `true` if the response is expired at the given point in time.
+    pub fn is_expired_at(&self, now: &DvbTime) -> bool {
+        self.expiration_time
+            .as_ref()
+            .is_none_or(|expiration| expiration.to_datetime() <= now.to_datetime())
+    }
+
+    /// Returns how long the response stays fresh, or `None` if it has no expiration time.
+    ///
+    /// Already expired responses have a time to live of zero.
+    pub fn time_to_live(&self) -> Option<chrono::Duration> {
+        self.time_to_live_at(&DvbTime::now())
+    }
+
+    /// Returns how long the response stays fresh, counted from the given point in time.
+    pub fn time_to_live_at(&self, now: &DvbTime) -> Option<chrono::Duration> {
+        self.expiration_time.as_ref().map(|expiration| {
+            (expiration.to_datetime() - now.to_datetime()).max(chrono::Duration::zero())
+        })
+    }
+
+    /// Returns the response unchanged if its status is `Ok`, otherwise an [`Error::Status`].
+    ///
+    /// Useful for responses obtained from a client created with
+    /// [`DvbClient::with_status_check(false)`](crate::DvbClient::with_status_check).
+    pub fn error_for_status(self) -> Result<Self> {
+        self.status.error_for_status()?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::lines::Lines;
+
+    #[test]
+    fn platform_defaults() {
+        let platform: Platform =
+            serde_json::from_str(r#"{"Name":"3","Type":"Railtrack"}"#).unwrap();
+        assert_eq!(platform.r#type, PlatformType::Railtrack);
+
+        let platform: Platform = serde_json::from_str(r#"{"Name":"A"}"#).unwrap();
+        assert_eq!(platform.r#type, PlatformType::Unknown);
+    }
+
+    #[test]
+    fn occupancy_fallback() {
+        let occupancy: Vec<Occupancy> =
+            serde_json::from_str(r#"["ManySeats","StandingOnly","Full","Overcrowded"]"#).unwrap();
+        assert_eq!(
+            occupancy,
+            [
+                Occupancy::ManySeats,
+                Occupancy::StandingOnly,
+                Occupancy::Full,
+                Occupancy::Unknown
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_expiration_time() {
+        let response: DvbResponse<Lines> =
+            serde_json::from_str(include_str!("../lines.json")).unwrap();
+        let expiration = response.expiration_time.as_ref().unwrap();
+
+        assert_eq!(expiration.timestamp_millis(), 1753008488015);
+        assert!(response.status.is_ok());
+    }
+
+    #[test]
+    fn freshness() {
+        let response: DvbResponse<Lines> =
+            serde_json::from_str(include_str!("../lines.json")).unwrap();
+
+        let before = DvbTime::from_str("/Date(1753008428015+0200)/").unwrap();
+        assert!(!response.is_expired_at(&before));
+        assert_eq!(
+            response.time_to_live_at(&before),
+            Some(chrono::Duration::seconds(60))
+        );
+
+        let after = DvbTime::from_str("/Date(1753008548015+0200)/").unwrap();
+        assert!(response.is_expired_at(&after));
+        assert_eq!(
+            response.time_to_live_at(&after),
+            Some(chrono::Duration::zero())
+        );
+        assert!(response.is_expired());
+    }
 }
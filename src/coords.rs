@@ -0,0 +1,312 @@
+//! Gauss-Krüger coordinates as used by the VVO WebAPI and their conversion to WGS84.
+//!
+//! The API reports positions as integer Gauss-Krüger coordinates in zone 4
+//! on the Bessel ellipsoid (DHDN/Potsdam datum), e.g. `5657516|4621644` for Hauptbahnhof.
+//! Mapping libraries expect WGS84 degrees instead, so [`Coordinate`] converts in both directions,
+//! including the datum shift between the two ellipsoids.
+
+use std::f64::consts::PI;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Bessel 1841 ellipsoid, used by DHDN.
+const BESSEL_A: f64 = 6_377_397.155;
+const BESSEL_F: f64 = 1.0 / 299.152_812_8;
+
+/// WGS84 ellipsoid.
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Zone the VVO WebAPI uses for all of its coordinates.
+const GK_ZONE: i64 = 4;
+
+/// Helmert parameters DHDN → WGS84 (position vector convention, EPSG:1777),
+/// translations in metres, rotations in arc seconds, scale in ppm.
+const HELMERT: [f64; 7] = [598.1, 73.7, 418.2, 0.202, 0.045, -2.455, 6.7];
+
+/// A position in the Gauss-Krüger (GK4) system of the VVO WebAPI.
+///
+/// The API calls the northing `Latitude` and the easting `Longitude`,
+/// which is kept when (de)serializing so that responses round-trip unchanged.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Coordinate {
+    /// Northing ("Hochwert") in metres.
+    #[serde(rename = "Latitude", deserialize_with = "deserialize_metres")]
+    #[schemars(schema_with = "metres_schema")]
+    pub northing: i64,
+    /// Easting ("Rechtswert") in metres, prefixed with the zone number 4 even far east of the zone.
+    #[serde(rename = "Longitude", deserialize_with = "deserialize_metres")]
+    #[schemars(schema_with = "metres_schema")]
+    pub easting: i64,
+}
+
+/// A position in WGS84 degrees, as used by GPS and web maps.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Wgs84 {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Wgs84 {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Great-circle distance to `other` in metres.
+    pub fn distance_to(&self, other: &Wgs84) -> f64 {
+        const EARTH_RADIUS: f64 = 6_371_008.8;
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+        let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * h.sqrt().asin()
+    }
+}
+
+impl Coordinate {
+    pub fn new(northing: i64, easting: i64) -> Self {
+        Self { northing, easting }
+    }
+
+    /// The API reports `0|0` for points whose position it doesn't know.
+    pub fn is_unknown(&self) -> bool {
+        self.northing == 0 && self.easting == 0
+    }
+
+    /// Converts to WGS84 degrees.
+    ///
+    /// Returns `None` for unknown positions (see [`Coordinate::is_unknown`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::coords::Coordinate;
+    /// let hauptbahnhof = Coordinate::new(5657516, 4621644).to_wgs84().unwrap();
+    /// assert!((hauptbahnhof.lat - 51.040).abs() < 0.001);
+    /// assert!((hauptbahnhof.lon - 13.733).abs() < 0.001);
+    /// ```
+    pub fn to_wgs84(&self) -> Option<Wgs84> {
+        if self.is_unknown() {
+            return None;
+        }
+        let (lat, lon) = gk_to_geographic(self.northing as f64, self.easting as f64, GK_ZONE);
+        let (x, y, z) = geographic_to_cartesian(lat, lon, BESSEL_A, BESSEL_F);
+        let (x, y, z) = helmert(x, y, z, 1.0);
+        let (lat, lon) = cartesian_to_geographic(x, y, z, WGS84_A, WGS84_F);
+        Some(Wgs84::new(lat.to_degrees(), lon.to_degrees()))
+    }
+
+    /// Converts WGS84 degrees to the nearest GK4 coordinate.
+    pub fn from_wgs84(position: Wgs84) -> Self {
+        let (x, y, z) = geographic_to_cartesian(
+            position.lat.to_radians(),
+            position.lon.to_radians(),
+            WGS84_A,
+            WGS84_F,
+        );
+        let (x, y, z) = helmert(x, y, z, -1.0);
+        let (lat, lon) = cartesian_to_geographic(x, y, z, BESSEL_A, BESSEL_F);
+        let (northing, easting) = geographic_to_gk(lat, lon, GK_ZONE);
+        Self::new(northing.round() as i64, easting.round() as i64)
+    }
+}
+
+impl From<Wgs84> for Coordinate {
+    fn from(position: Wgs84) -> Self {
+        Self::from_wgs84(position)
+    }
+}
+
+/// Written as integer metres, read from integers and floats alike.
+fn metres_schema(generator: &mut SchemaGenerator) -> Schema {
+    if generator.contract().is_serialize() {
+        return json_schema!({ "type": "integer", "format": "int64" });
+    }
+    json_schema!({ "type": "number" })
+}
+
+/// Accepts both integer and floating point metres.
+fn deserialize_metres<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Metres {
+        Int(i64),
+        Float(f64),
+    }
+
+    Ok(match Metres::deserialize(deserializer)? {
+        Metres::Int(metres) => metres,
+        Metres::Float(metres) => metres.round() as i64,
+    })
+}
+
+fn eccentricity_squared(f: f64) -> f64 {
+    2.0 * f - f * f
+}
+
+fn central_meridian(zone: i64) -> f64 {
+    (zone as f64 * 3.0).to_radians()
+}
+
+/// Inverse transverse Mercator projection on the Bessel ellipsoid.
+fn gk_to_geographic(northing: f64, easting: f64, zone: i64) -> (f64, f64) {
+    let a = BESSEL_A;
+    let e2 = eccentricity_squared(BESSEL_F);
+    let ep2 = e2 / (1.0 - e2);
+    let x = easting - (zone as f64 * 1_000_000.0 + 500_000.0);
+
+    let mu = northing / (a * (1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1.powi(2) / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin1, cos1, tan1) = (phi1.sin(), phi1.cos(), phi1.tan());
+    let c1 = ep2 * cos1.powi(2);
+    let t1 = tan1.powi(2);
+    let n1 = a / (1.0 - e2 * sin1.powi(2)).sqrt();
+    let r1 = a * (1.0 - e2) / (1.0 - e2 * sin1.powi(2)).powf(1.5);
+    let d = x / n1;
+
+    let lat = phi1
+        - (n1 * tan1 / r1)
+            * (d.powi(2) / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1.powi(2) - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1.powi(2)
+                    - 252.0 * ep2
+                    - 3.0 * c1.powi(2))
+                    * d.powi(6)
+                    / 720.0);
+    let lon = central_meridian(zone)
+        + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+            + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1.powi(2) + 8.0 * ep2 + 24.0 * t1.powi(2))
+                * d.powi(5)
+                / 120.0)
+            / cos1;
+
+    (lat, lon)
+}
+
+/// Transverse Mercator projection on the Bessel ellipsoid.
+fn geographic_to_gk(lat: f64, lon: f64, zone: i64) -> (f64, f64) {
+    let a = BESSEL_A;
+    let e2 = eccentricity_squared(BESSEL_F);
+    let ep2 = e2 / (1.0 - e2);
+
+    let (sin, cos, tan) = (lat.sin(), lat.cos(), lat.tan());
+    let n = a / (1.0 - e2 * sin.powi(2)).sqrt();
+    let t = tan.powi(2);
+    let c = ep2 * cos.powi(2);
+    let big_a = (lon - central_meridian(zone)) * cos;
+    let m = a
+        * ((1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0) * lat
+            - (3.0 * e2 / 8.0 + 3.0 * e2.powi(2) / 32.0 + 45.0 * e2.powi(3) / 1024.0)
+                * (2.0 * lat).sin()
+            + (15.0 * e2.powi(2) / 256.0 + 45.0 * e2.powi(3) / 1024.0) * (4.0 * lat).sin()
+            - (35.0 * e2.powi(3) / 3072.0) * (6.0 * lat).sin());
+
+    let x = n
+        * (big_a
+            + (1.0 - t + c) * big_a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t.powi(2) + 72.0 * c - 58.0 * ep2) * big_a.powi(5) / 120.0);
+    let y = m + n
+        * tan
+        * (big_a.powi(2) / 2.0
+            + (5.0 - t + 9.0 * c + 4.0 * c.powi(2)) * big_a.powi(4) / 24.0
+            + (61.0 - 58.0 * t + t.powi(2) + 600.0 * c - 330.0 * ep2) * big_a.powi(6) / 720.0);
+
+    (y, x + zone as f64 * 1_000_000.0 + 500_000.0)
+}
+
+fn geographic_to_cartesian(lat: f64, lon: f64, a: f64, f: f64) -> (f64, f64, f64) {
+    let e2 = eccentricity_squared(f);
+    let n = a / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+    (
+        n * lat.cos() * lon.cos(),
+        n * lat.cos() * lon.sin(),
+        n * (1.0 - e2) * lat.sin(),
+    )
+}
+
+fn cartesian_to_geographic(x: f64, y: f64, z: f64, a: f64, f: f64) -> (f64, f64) {
+    let e2 = eccentricity_squared(f);
+    let p = x.hypot(y);
+    let mut lat = z.atan2(p * (1.0 - e2));
+    for _ in 0..10 {
+        let n = a / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        let h = p / lat.cos() - n;
+        lat = z.atan2(p * (1.0 - e2 * n / (n + h)));
+    }
+    (lat, y.atan2(x))
+}
+
+/// Seven parameter datum shift, `direction` is `1.0` for DHDN → WGS84 and `-1.0` for the inverse.
+fn helmert(x: f64, y: f64, z: f64, direction: f64) -> (f64, f64, f64) {
+    let arc_second = PI / (180.0 * 3600.0);
+    let [tx, ty, tz, rx, ry, rz, s] = HELMERT.map(|p| p * direction);
+    let (rx, ry, rz) = (rx * arc_second, ry * arc_second, rz * arc_second);
+    let scale = 1.0 + s * 1e-6;
+    (
+        tx + scale * (x - rz * y + ry * z),
+        ty + scale * (rz * x + y - rx * z),
+        tz + scale * (-ry * x + rx * y + z),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hauptbahnhof_to_wgs84() {
+        let position = Coordinate::new(5657516, 4621644).to_wgs84().unwrap();
+        // 51°2′24″N 13°43′57″E
+        assert!((position.lat - 51.0400).abs() < 0.001, "{position:?}");
+        assert!((position.lon - 13.7325).abs() < 0.001, "{position:?}");
+    }
+
+    #[test]
+    fn round_trip_is_lossless() {
+        for (northing, easting) in [
+            (5657516, 4621644),
+            (5659353, 4622195),
+            (5674722, 4852808),
+            (5642476, 4083477),
+            (5577999, 5068401),
+        ] {
+            let coordinate = Coordinate::new(northing, easting);
+            let position = coordinate.to_wgs84().unwrap();
+            assert_eq!(Coordinate::from_wgs84(position), coordinate);
+        }
+    }
+
+    #[test]
+    fn far_east_stays_in_zone_4() {
+        // Kraków Główny, reported in zone 4 like every other stop
+        let position = Coordinate::new(5577999, 5068401).to_wgs84().unwrap();
+        assert!((position.lat - 50.06).abs() < 0.01, "{position:?}");
+        assert!((position.lon - 19.94).abs() < 0.01, "{position:?}");
+    }
+
+    #[test]
+    fn distance() {
+        let hauptbahnhof = Wgs84::new(51.0400, 13.7325);
+        let postplatz = Wgs84::new(51.0507, 13.7335);
+        let distance = hauptbahnhof.distance_to(&postplatz);
+        assert!((distance - 1192.0).abs() < 5.0, "{distance}");
+    }
+
+    #[test]
+    fn unknown_position() {
+        assert_eq!(Coordinate::new(0, 0).to_wgs84(), None);
+    }
+
+    #[test]
+    fn deserialize_integer_and_float_metres() {
+        let coordinate: Coordinate =
+            serde_json::from_str(r#"{"Latitude":5657516,"Longitude":4621644.0}"#).unwrap();
+        assert_eq!(coordinate, Coordinate::new(5657516, 4621644));
+    }
+}
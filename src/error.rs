@@ -3,10 +3,19 @@
 use reqwest;
 use std::{fmt, io};
 
+use crate::common::StatusCode;
+
 #[derive(Debug)]
 pub enum Error {
     ApiError,
+    /// The service answered with a status other than [`StatusCode::Ok`].
+    Status {
+        code: StatusCode,
+        message: Option<String>,
+    },
     DateParse,
+    /// Routes to page from carry neither a session nor departure and arrival times.
+    NoPagingReference,
     Io(io::Error),
     Reqwest(reqwest::Error),
     Serde(serde_json::Error),
@@ -18,7 +27,18 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::ApiError => write!(f, "unexpected response from service"),
+            Error::Status {
+                code,
+                message: Some(message),
+            } => write!(f, "service responded with {code:?}: {message}"),
+            Error::Status {
+                code,
+                message: None,
+            } => write!(f, "service responded with {code:?}"),
             Error::DateParse => write!(f, "can't parse date"),
+            Error::NoPagingReference => {
+                write!(f, "routes have neither a session nor times to page from")
+            }
             Error::Reqwest(error) => write!(f, "{error}"),
             Error::Io(error) => write!(f, "{error}"),
             Error::Serde(error) => write!(f, "{error}"),
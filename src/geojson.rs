@@ -0,0 +1,283 @@
+//! Conversion of stops, trips and routes into GeoJSON for web maps.
+//!
+//! Requires the `geojson` feature. All positions are converted from GK4 to WGS84,
+//! unknown positions are left out.
+//!
+//! # Example
+//! ```no_run
+//! # async fn run() -> dvb::Result<()> {
+//! use dvb::geojson::ToGeoJson;
+//!
+//! let found = dvb::find_stops("Postplatz").await?;
+//! println!("{}", found.to_geojson());
+//! # Ok(())
+//! # }
+//! ```
+
+use ::geojson::{Feature, FeatureCollection, Geometry, JsonObject, JsonValue, Value};
+
+use crate::{
+    coords::{Coordinate, Wgs84},
+    monitor::Departure,
+    point::Found,
+    route::{PartialRoute, RegularStop, Route},
+    time::DvbTime,
+    trip::Trip,
+};
+
+/// Types that can be drawn on a map as a GeoJSON [`FeatureCollection`].
+pub trait ToGeoJson {
+    fn to_geojson(&self) -> FeatureCollection;
+}
+
+fn position(wgs84: Wgs84) -> Vec<f64> {
+    vec![wgs84.lon, wgs84.lat]
+}
+
+fn point(coords: &Coordinate) -> Option<Geometry> {
+    coords
+        .to_wgs84()
+        .map(|wgs84| Geometry::new(Value::Point(position(wgs84))))
+}
+
+fn line_string(points: impl IntoIterator<Item = Wgs84>) -> Option<Geometry> {
+    let positions: Vec<Vec<f64>> = points.into_iter().map(position).collect();
+    (positions.len() >= 2).then(|| Geometry::new(Value::LineString(positions)))
+}
+
+fn time(time: Option<&DvbTime>) -> JsonValue {
+    time.map_or(JsonValue::Null, |time| time.to_rfc3339().into())
+}
+
+fn feature(geometry: Option<Geometry>, properties: JsonObject) -> Feature {
+    Feature {
+        geometry,
+        properties: Some(properties),
+        ..Default::default()
+    }
+}
+
+fn collection(features: Vec<Feature>) -> FeatureCollection {
+    FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    }
+}
+
+/// One `Point` feature per found point, with its id, name, city and type.
+impl ToGeoJson for Found {
+    fn to_geojson(&self) -> FeatureCollection {
+        let features = self
+            .points
+            .iter()
+            .map(|found| {
+                let mut properties = JsonObject::new();
+                properties.insert("id".into(), found.id.clone().into());
+                properties.insert("name".into(), found.name.clone().into());
+                properties.insert("city".into(), found.city.clone().into());
+                properties.insert(
+                    "type".into(),
+                    serde_json::to_value(found.r#type).unwrap_or_default(),
+                );
+                feature(point(&found.coords), properties)
+            })
+            .collect();
+        collection(features)
+    }
+}
+
+fn trip_collection(trip: &Trip, line: Option<&str>, direction: Option<&str>) -> FeatureCollection {
+    let mut features: Vec<Feature> = trip
+        .stops
+        .iter()
+        .map(|stop| {
+            let mut properties = JsonObject::new();
+            properties.insert("id".into(), stop.id.clone().into());
+            properties.insert("name".into(), stop.name.clone().into());
+            properties.insert("place".into(), stop.place.clone().into());
+            properties.insert("platform".into(), stop.platform.name.clone().into());
+            properties.insert("time".into(), time(Some(&stop.time)));
+            properties.insert("real_time".into(), time(stop.real_time.as_ref()));
+            feature(point(&stop.coords), properties)
+        })
+        .collect();
+
+    if let Some(course) = line_string(trip.stops.iter().filter_map(|stop| stop.coords.to_wgs84())) {
+        let direction = direction.or(trip.stops.last().map(|stop| stop.name.as_str()));
+        let mut properties = JsonObject::new();
+        properties.insert("line".into(), line.into());
+        properties.insert("direction".into(), direction.into());
+        features.push(feature(Some(course), properties));
+    }
+    collection(features)
+}
+
+/// One `Point` feature per stop, followed by a `LineString` feature connecting all stops
+/// if at least two positions are known.
+///
+/// The trip response doesn't name its line, so the `line` property of the `LineString` is `null`
+/// and its `direction` is the last stop. Use [`trip_for_departure`] to fill in both.
+impl ToGeoJson for Trip {
+    fn to_geojson(&self) -> FeatureCollection {
+        trip_collection(self, None, None)
+    }
+}
+
+/// Like [`Trip::to_geojson`], with line and direction taken from the departure the trip was requested for.
+pub fn trip_for_departure(trip: &Trip, departure: &Departure) -> FeatureCollection {
+    trip_collection(trip, Some(&departure.line_name), Some(&departure.direction))
+}
+
+/// The course of a partial route: its map data if available, otherwise its stops.
+fn course(partial: &PartialRoute, route: &Route) -> Option<Geometry> {
+    match partial.geometry(route) {
+        Some(polyline) => line_string(polyline.to_wgs84()),
+        None => line_string(
+            partial
+                .regular_stops
+                .iter()
+                .flatten()
+                .filter_map(|stop| stop.coords.as_ref()?.to_wgs84()),
+        ),
+    }
+}
+
+/// One `LineString` feature per partial route, with its mode, line, direction and times.
+impl ToGeoJson for Route {
+    fn to_geojson(&self) -> FeatureCollection {
+        let features = self
+            .partial_routes
+            .iter()
+            .flatten()
+            .map(|partial| {
+                let stops = partial.regular_stops.as_deref().unwrap_or_default();
+                let departure = stops.first().and_then(RegularStop::departure);
+                let arrival = stops.last().and_then(RegularStop::arrival);
+                let mot = partial.mot.as_ref();
+
+                let mut properties = JsonObject::new();
+                properties.insert(
+                    "mode".into(),
+                    serde_json::to_value(partial.mode()).unwrap_or_default(),
+                );
+                properties.insert("line".into(), mot.and_then(|mot| mot.name.clone()).into());
+                properties.insert(
+                    "direction".into(),
+                    mot.and_then(|mot| mot.direction.clone()).into(),
+                );
+                properties.insert("departure".into(), time(departure));
+                properties.insert("arrival".into(), time(arrival));
+                feature(course(partial, self), properties)
+            })
+            .collect();
+        collection(features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn found_points() {
+        let found: Found = serde_json::from_value(serde_json::json!({
+            "PointStatus": "List",
+            "Points": ["33000028||Dresden|Hauptbahnhof|5657516|4621644|0||"],
+        }))
+        .unwrap();
+        let collection = found.to_geojson();
+
+        let feature = &collection.features[0];
+        let Some(Value::Point(position)) = feature.geometry.as_ref().map(|g| &g.value) else {
+            panic!("expected a point, got {:?}", feature.geometry);
+        };
+        assert!((position[0] - 13.7325).abs() < 0.001);
+        assert!((position[1] - 51.0400).abs() < 0.001);
+        assert_eq!(feature.property("name"), Some(&"Hauptbahnhof".into()));
+    }
+
+    fn trip(positions: &[(i64, i64)]) -> Trip {
+        let stops: Vec<_> = positions
+            .iter()
+            .enumerate()
+            .map(|(index, (northing, easting))| {
+                serde_json::json!({
+                    "Id": format!("3300002{index}"),
+                    "Name": format!("Stop {index}"),
+                    "Place": "Dresden",
+                    "Platform": { "Name": "1", "Type": "Platform" },
+                    "Latitude": northing,
+                    "Longitude": easting,
+                    "Position": "Next",
+                    "Time": "/Date(1753008480000+0200)/",
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({ "Stops": stops })).unwrap()
+    }
+
+    #[test]
+    fn trip_stops_and_course() {
+        let collection = trip(&[(5657516, 4621644), (5658012, 4621101), (0, 0)]).to_geojson();
+
+        assert_eq!(collection.features.len(), 4);
+        assert!(collection.features[2].geometry.is_none());
+        let course = &collection.features[3];
+        assert!(matches!(
+            course.geometry.as_ref().map(|g| &g.value),
+            Some(Value::LineString(positions)) if positions.len() == 2
+        ));
+        assert_eq!(course.property("direction"), Some(&"Stop 2".into()));
+        assert_eq!(course.property("line"), Some(&JsonValue::Null));
+
+        let departure: Departure = serde_json::from_value(serde_json::json!({
+            "Id": "voe:11003: :H:j25",
+            "LineName": "3",
+            "Direction": "Wilder Mann",
+            "Mot": "Tram",
+        }))
+        .unwrap();
+        let collection =
+            trip_for_departure(&trip(&[(5657516, 4621644), (5658012, 4621101)]), &departure);
+        let course = collection.features.last().unwrap();
+        assert_eq!(course.property("line"), Some(&"3".into()));
+        assert_eq!(course.property("direction"), Some(&"Wilder Mann".into()));
+    }
+
+    #[test]
+    fn trip_without_course() {
+        let collection = trip(&[(5657516, 4621644)]).to_geojson();
+        assert_eq!(collection.features.len(), 1);
+    }
+
+    #[test]
+    fn route_legs() {
+        let route: Route = serde_json::from_value(serde_json::json!({
+            "MapData": ["Tram|5657516|4621644|5658012|4621101|"],
+            "PartialRoutes": [
+                {
+                    "MapDataIndex": 0,
+                    "Mot": { "Type": "Tram", "Name": "3", "Direction": "Wilder Mann" },
+                    "RegularStops": [
+                        { "DepartureTime": "/Date(1753008480000+0200)/", "CancelReasons": [] },
+                        { "ArrivalTime": "/Date(1753009080000+0200)/", "CancelReasons": [] },
+                    ]
+                },
+                { "Mot": { "Type": "Footpath" } },
+            ]
+        }))
+        .unwrap();
+        let collection = route.to_geojson();
+
+        assert_eq!(collection.features.len(), 2);
+        let tram = &collection.features[0];
+        assert!(matches!(
+            tram.geometry.as_ref().map(|g| &g.value),
+            Some(Value::LineString(positions)) if positions.len() == 2
+        ));
+        assert_eq!(tram.property("mode"), Some(&"Tram".into()));
+        assert_eq!(tram.property("line"), Some(&"3".into()));
+        assert!(collection.features[1].geometry.is_none());
+    }
+}
@@ -0,0 +1,335 @@
+//! iCalendar (RFC 5545) export of planned journeys.
+
+use std::fmt::Write;
+
+use chrono::Utc;
+
+use crate::{
+    common::ArrivalState,
+    route::{PartialRoute, RegularStop, Route},
+    time::DvbTime,
+};
+
+const PRODID: &str = "-//dvb-rs//dvb//DE";
+
+fn utc(time: &DvbTime) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escapes a TEXT value, newlines become `\n`.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folded after 75 octets as the RFC demands.
+fn push_line(ics: &mut String, name: &str, value: &str) {
+    let line = format!("{name}:{value}");
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+fn stop_name(stop: &RegularStop) -> String {
+    match (stop.name.as_deref(), stop.place.as_deref()) {
+        (Some(name), Some(place)) if !place.is_empty() => format!("{name}, {place}"),
+        (name, _) => name.unwrap_or("?").to_string(),
+    }
+}
+
+fn stops(partial: &PartialRoute) -> &[RegularStop] {
+    partial.regular_stops.as_deref().unwrap_or_default()
+}
+
+fn is_cancelled(partial: &PartialRoute) -> bool {
+    partial.trip_cancelled == Some(true)
+        || stops(partial).iter().any(|stop| {
+            stop.departure_state == Some(ArrivalState::Cancelled)
+                || stop.arrival_state == Some(ArrivalState::Cancelled)
+        })
+}
+
+/// Line and direction of a partial route, e.g. `3 Wilder Mann`.
+fn title(partial: &PartialRoute) -> String {
+    let Some(mot) = &partial.mot else {
+        return String::new();
+    };
+    let name = mot.name.as_deref().or(mot.product_name.as_deref());
+    let mode = partial
+        .mode()
+        .and_then(|mode| serde_json::to_value(mode).ok())
+        .and_then(|mode| mode.as_str().map(String::from));
+    let mut title = name.map(String::from).or(mode).unwrap_or_default();
+    if let Some(direction) = &mot.direction {
+        write!(title, " {direction}").ok();
+    }
+    title
+}
+
+/// One line of the description, e.g. `3 Wilder Mann: 10:48 Hauptbahnhof (3) - 11:02 Albertplatz`.
+fn describe(partial: &PartialRoute) -> Option<String> {
+    let stops = stops(partial);
+    let (first, last) = (stops.first()?, stops.last()?);
+    let time = |time: Option<&DvbTime>| time.map(|t| t.format("%H:%M").to_string());
+
+    let mut line = title(partial);
+    if !line.is_empty() {
+        line.push_str(": ");
+    }
+    if let Some(time) = time(first.departure()) {
+        write!(line, "{time} ").ok();
+    }
+    line.push_str(&stop_name(first));
+    if let Some(platform) = first.platform.as_ref().filter(|p| !p.name.is_empty()) {
+        write!(line, " ({})", platform.name).ok();
+    }
+    line.push_str(" - ");
+    if let Some(time) = time(last.arrival()) {
+        write!(line, "{time} ").ok();
+    }
+    line.push_str(&stop_name(last));
+    if let Some(platform) = last.platform.as_ref().filter(|p| !p.name.is_empty()) {
+        write!(line, " ({})", platform.name).ok();
+    }
+    Some(line)
+}
+
+struct Event<'a> {
+    uid: String,
+    start: &'a DvbTime,
+    end: &'a DvbTime,
+    summary: String,
+    location: String,
+    description: String,
+    cancelled: bool,
+}
+
+impl Event<'_> {
+    fn push_to(&self, ics: &mut String, stamp: &str) {
+        push_line(ics, "BEGIN", "VEVENT");
+        push_line(ics, "UID", &self.uid);
+        push_line(ics, "DTSTAMP", stamp);
+        push_line(ics, "DTSTART", &utc(self.start));
+        push_line(ics, "DTEND", &utc(self.end));
+        push_line(ics, "SUMMARY", &escape(&self.summary));
+        push_line(ics, "LOCATION", &escape(&self.location));
+        push_line(ics, "DESCRIPTION", &escape(&self.description));
+        let status = if self.cancelled {
+            "CANCELLED"
+        } else {
+            "CONFIRMED"
+        };
+        push_line(ics, "STATUS", status);
+        push_line(ics, "END", "VEVENT");
+    }
+}
+
+fn calendar(events: &[Event<'_>]) -> String {
+    let stamp = utc(&DvbTime::now());
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN", "VCALENDAR");
+    push_line(&mut ics, "VERSION", "2.0");
+    push_line(&mut ics, "PRODID", PRODID);
+    for event in events {
+        event.push_to(&mut ics, &stamp);
+    }
+    push_line(&mut ics, "END", "VCALENDAR");
+    ics
+}
+
+impl Route {
+    fn uid(&self, start: &DvbTime, part: Option<usize>) -> String {
+        let route_id = self.route_id.unwrap_or_default();
+        match part {
+            Some(part) => format!("{}-{route_id}-{part}@dvb-rs", start.timestamp()),
+            None => format!("{}-{route_id}@dvb-rs", start.timestamp()),
+        }
+    }
+
+    fn first_and_last_stop(&self) -> Option<(&RegularStop, &RegularStop)> {
+        let mut stops = self.partial_routes.iter().flatten().flat_map(stops);
+        let first = stops.next()?;
+        Some((first, stops.last().unwrap_or(first)))
+    }
+
+    /// Exports the journey as an iCalendar file with a single event.
+    ///
+    /// The event lasts from the departure at the first stop until the arrival at the last,
+    /// describes every partial route with line and platforms and is `CANCELLED` if any of them is.
+    /// Returns `None` if the route has no stops with times.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn run() -> dvb::Result<()> {
+    /// use dvb::{DvbTime, route::{Params, route_details}};
+    ///
+    /// let routes = route_details(&Params::new("33000028", "33000016", DvbTime::now())).await?;
+    /// if let Some(ics) = routes.routes.first().and_then(|route| route.to_ics()) {
+    ///     std::fs::write("journey.ics", ics)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_ics(&self) -> Option<String> {
+        let (first, last) = self.first_and_last_stop()?;
+        let (start, end) = (self.departure_time()?, self.arrival_time()?);
+        let partials = self.partial_routes.iter().flatten();
+
+        let event = Event {
+            uid: self.uid(start, None),
+            start,
+            end,
+            summary: format!("{} - {}", stop_name(first), stop_name(last)),
+            location: stop_name(first),
+            description: partials
+                .clone()
+                .filter_map(describe)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            cancelled: self.route_cancelled == Some(true) || partials.clone().any(is_cancelled),
+        };
+        Some(calendar(&[event]))
+    }
+
+    /// Exports the journey as an iCalendar file with one event per partial route.
+    ///
+    /// Partial routes without stops or times, like some footpaths, are left out.
+    /// Returns `None` if that leaves no events.
+    pub fn to_ics_per_partial_route(&self) -> Option<String> {
+        let events: Vec<Event<'_>> = self
+            .partial_routes
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, partial)| {
+                let stops = stops(partial);
+                let (first, last) = (stops.first()?, stops.last()?);
+                let (start, end) = (first.departure()?, last.arrival()?);
+                let description = describe(partial)?;
+                let title = title(partial);
+                let mut summary = format!("{} - {}", stop_name(first), stop_name(last));
+                if !title.is_empty() {
+                    summary = format!("{title}: {summary}");
+                }
+                Some(Event {
+                    uid: self.uid(start, Some(index)),
+                    start,
+                    end,
+                    summary,
+                    location: stop_name(first),
+                    description,
+                    cancelled: self.route_cancelled == Some(true) || is_cancelled(partial),
+                })
+            })
+            .collect();
+        (!events.is_empty()).then(|| calendar(&events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(cancelled: bool) -> Route {
+        let state = if cancelled { "Cancelled" } else { "InTime" };
+        serde_json::from_value(serde_json::json!({
+            "RouteId": 1,
+            "PartialRoutes": [
+                {
+                    "Mot": { "Type": "Tram", "Name": "3", "Direction": "Wilder Mann" },
+                    "RegularStops": [
+                        {
+                            "Name": "Hauptbahnhof", "Place": "Dresden",
+                            "DepartureTime": "/Date(1753008480000+0200)/",
+                            "DepartureState": state,
+                            "Platform": { "Name": "3", "Type": "Platform" },
+                            "CancelReasons": [],
+                        },
+                        {
+                            "Name": "Albertplatz", "Place": "Dresden",
+                            "ArrivalTime": "/Date(1753009320000+0200)/",
+                            "CancelReasons": [],
+                        },
+                    ]
+                },
+                { "Mot": { "Type": "Footpath" } },
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn single_event() {
+        let ics = route(false).to_ics().unwrap();
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"DTSTART:20250720T104800Z"));
+        assert!(lines.contains(&"DTEND:20250720T110200Z"));
+        assert!(lines.contains(&"SUMMARY:Hauptbahnhof\\, Dresden - Albertplatz\\, Dresden"));
+        assert!(lines.contains(&"STATUS:CONFIRMED"));
+        assert!(ics.contains("3 Wilder Mann: 12:48 Hauptbahnhof\\, Dresden (3)"));
+        assert!(lines.iter().all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn cancelled_partial_route() {
+        let route = route(true);
+        assert!(route.to_ics().unwrap().contains("STATUS:CANCELLED"));
+
+        let ics = route.to_ics_per_partial_route().unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("SUMMARY:3 Wilder Mann: Hauptbahnhof"));
+    }
+
+    #[test]
+    fn footpath_only() {
+        let route: Route = serde_json::from_value(serde_json::json!({
+            "PartialRoutes": [{ "Mot": { "Type": "Footpath" } }]
+        }))
+        .unwrap();
+        assert_eq!(route.to_ics(), None);
+        assert_eq!(route.to_ics_per_partial_route(), None);
+    }
+
+    #[test]
+    fn title_falls_back_to_mode() {
+        let partial: PartialRoute = serde_json::from_value(serde_json::json!({
+            "Mot": { "Type": "SuburbanRailway", "Direction": "Meißen" }
+        }))
+        .unwrap();
+        assert_eq!(title(&partial), "SuburbanRailway Meißen");
+    }
+
+    #[test]
+    fn escape_and_fold() {
+        assert_eq!(escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+
+        let mut ics = String::new();
+        push_line(&mut ics, "DESCRIPTION", &"ä".repeat(50));
+        let lines: Vec<&str> = ics.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+}
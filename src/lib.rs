@@ -6,8 +6,17 @@
 //! `http://widgets.vvo-online.de/abfahrtsmonitor/Haltestelle.do`
 //!
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod client;
 mod common;
+pub mod coords;
 pub mod error;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+mod ical;
+mod price;
 mod time;
 
 pub mod lines;
@@ -17,16 +26,23 @@ pub mod point;
 pub mod route;
 pub mod route_changes;
 pub mod trip;
+pub mod watch;
 
 pub use crate::{
-    common::{DvbResponse, Mot},
+    client::DvbClient,
+    common::{
+        ArrivalState, Diva, DvbResponse, Mot, Occupancy, Platform, PlatformType, Status, StatusCode,
+    },
+    coords::{Coordinate, Wgs84},
     error::Result,
+    price::{Price, PriceParseError},
     time::DvbTime,
 };
 
 use crate::{
+    client::default_client,
     monitor::DepartureMonitor,
-    point::{Found, Params, point_finder},
+    point::{Found, Nearby},
 };
 
 /// Search for stops by name using the VVO PointFinder API.
@@ -36,12 +52,7 @@ use crate::{
 ///
 /// Endpoint: `https://webapi.vvo-online.de/pointfinder`
 pub async fn find_stops(query: &str) -> Result<DvbResponse<Found>> {
-    point_finder(&Params {
-        query,
-        stops_only: true,
-        ..Default::default()
-    })
-    .await
+    default_client().find_stops(query).await
 }
 
 /// Search for nearby and assigned stops using the VVO PointFinder API.
@@ -51,13 +62,17 @@ pub async fn find_stops(query: &str) -> Result<DvbResponse<Found>> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/pointfinder`
 pub async fn find_nearby_stops(query: &str) -> Result<DvbResponse<Found>> {
-    point_finder(&Params {
-        query,
-        stops_only: false,
-        assigedstops: true,
-        ..Default::default()
-    })
-    .await
+    default_client().find_nearby_stops(query).await
+}
+
+/// Search for stops within `radius` metres around a WGS84 position.
+///
+/// Converts the position to a `coord:` PointFinder query and returns the found stops
+/// sorted by distance, each with its distance in metres attached.
+///
+/// Endpoint: `https://webapi.vvo-online.de/tr/pointfinder`
+pub async fn find_stops_near(lat: f64, lon: f64, radius: f64) -> Result<DvbResponse<Nearby>> {
+    default_client().find_stops_near(lat, lon, radius).await
 }
 
 /// Search for points of interest (POIs) using the VVO PointFinder API.
@@ -67,12 +82,7 @@ pub async fn find_nearby_stops(query: &str) -> Result<DvbResponse<Found>> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/pointfinder`
 pub async fn find_pois(query: &str) -> Result<DvbResponse<Found>> {
-    point_finder(&Params {
-        query,
-        stops_only: false,
-        ..Default::default()
-    })
-    .await
+    default_client().find_pois(query).await
 }
 
 /// Get upcoming departures for a stop using the VVO Departure Monitor API.
@@ -82,13 +92,5 @@ pub async fn find_pois(query: &str) -> Result<DvbResponse<Found>> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/dm`
 pub async fn monitor_departures(stopid: &str) -> Result<DvbResponse<DepartureMonitor>> {
-    let monitor = monitor::departure_monitor(monitor::Params {
-        stopid,
-        mot: None,
-        limit: Some(15),
-        ..Default::default()
-    })
-    .await?;
-
-    Ok(monitor)
+    default_client().monitor_departures(stopid).await
 }
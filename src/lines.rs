@@ -1,11 +1,11 @@
 //! Types and API for querying available lines (trams, buses, etc.) at a stop.
 
-use std::{fmt::Debug, time::Duration};
+use std::fmt::Debug;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{DvbResponse, Mot, error::Result};
+use crate::{DvbResponse, Mot, client::default_client, common::Diva, error::Result};
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -15,13 +15,6 @@ pub struct Mode {
     icon_url: Option<String>,
 }
 
-#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Diva {
-    pub network: String,
-    pub number: Option<String>,
-}
-
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Lines {
@@ -54,7 +47,7 @@ pub struct TimeTable {
     name: String,
 }
 
-const LINES_URL: &str = "https://webapi.vvo-online.de/stt/lines";
+pub(crate) const LINES_PATH: &str = "/stt/lines";
 
 /// Fetches all lines (trams, buses, etc.) departing from the specified stop ID using the VVO WebAPI.
 ///
@@ -67,14 +60,5 @@ const LINES_URL: &str = "https://webapi.vvo-online.de/stt/lines";
 ///
 /// Endpoint: `https://webapi.vvo-online.de/stt/lines`
 pub async fn lines(stop_id: &str, timeout: Option<u64>) -> Result<DvbResponse<Lines>> {
-    let response: DvbResponse<Lines> = reqwest::Client::new()
-        .post(LINES_URL)
-        .json(&serde_json::json!({ "stopid": stop_id, "format": "json" }))
-        .timeout(Duration::from_millis(timeout.unwrap_or(15000)))
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(response)
+    default_client().lines(stop_id, timeout).await
 }
@@ -1,30 +1,18 @@
 //! Departure monitor API types and functions.
 
+use std::collections::HashSet;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     DvbResponse,
-    common::{ArrivalState, Mot},
+    client::default_client,
+    common::{ArrivalState, Diva, Mot, Occupancy, Platform},
     error::Result,
     time::DvbTime,
 };
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Platform {
-    pub name: String,
-    /// Either `"Platform"` or `"Railtrack"`.
-    pub r#type: String,
-}
-
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Diva {
-    pub number: String,
-    pub network: String,
-}
-
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Departure {
@@ -41,7 +29,30 @@ pub struct Departure {
     pub diva: Option<Diva>,
     #[serde(default)]
     pub cancel_reasons: Vec<String>,
-    pub occupancy: Option<String>,
+    pub occupancy: Option<Occupancy>,
+}
+
+impl Departure {
+    /// Returns how late (positive) or early (negative) the departure is.
+    ///
+    /// Departures without a real-time prognosis are considered on time.
+    pub fn delay(&self) -> chrono::Duration {
+        match (&self.real_time, &self.scheduled_time) {
+            (Some(real_time), Some(scheduled_time)) => real_time.duration_since(scheduled_time),
+            _ => chrono::Duration::zero(),
+        }
+    }
+
+    /// Returns the real-time prognosis, falling back to the scheduled time.
+    pub fn expected_time(&self) -> Option<&DvbTime> {
+        self.real_time.as_ref().or(self.scheduled_time.as_ref())
+    }
+
+    /// Returns the whole minutes from `now` until the expected departure.
+    pub fn minutes_until(&self, now: &DvbTime) -> Option<i64> {
+        self.expected_time()
+            .map(|expected| expected.duration_since(now).num_minutes())
+    }
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
@@ -62,16 +73,60 @@ impl DepartureMonitor {
     }
 }
 
-const MONITOR_URL: &str = "https://webapi.vvo-online.de/dm";
+/// A departure from one of several monitored stops, see [`aggregated_departure_monitor`].
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AggregatedDeparture {
+    /// The stop ID the departure was reported for.
+    pub stopid: String,
+    /// Name of that stop, as reported by its departure monitor.
+    pub stop_name: Option<String>,
+    #[serde(flatten)]
+    pub departure: Departure,
+}
+
+/// Merges the departures of several monitors, dropping duplicates by [`Departure::id`]
+/// and sorting by [`Departure::expected_time`].
+pub(crate) fn aggregate<'a>(
+    monitors: impl IntoIterator<Item = (&'a str, DepartureMonitor)>,
+) -> Vec<AggregatedDeparture> {
+    let mut seen = HashSet::new();
+    let mut departures: Vec<AggregatedDeparture> = monitors
+        .into_iter()
+        .flat_map(|(stopid, monitor)| {
+            let stop_name = monitor.name;
+            monitor
+                .departures
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |departure| AggregatedDeparture {
+                    stopid: stopid.to_string(),
+                    stop_name: stop_name.clone(),
+                    departure,
+                })
+        })
+        .filter(|aggregated| seen.insert(aggregated.departure.id.clone()))
+        .collect();
 
-#[derive(Serialize, Clone, Debug, Default)]
+    departures.sort_by_key(|aggregated| {
+        aggregated
+            .departure
+            .expected_time()
+            .map_or(i64::MAX, |time| time.timestamp())
+    });
+    departures
+}
+
+pub(crate) const MONITOR_PATH: &str = "/dm";
+
+#[derive(Serialize, JsonSchema, Clone, Debug, Default)]
 pub struct Params<'a> {
     /// The stop ID to monitor.
     pub stopid: &'a str,
     /// Maximum number of departures to return.
     pub limit: Option<u32>,
-    /// Time for the departure query.
-    pub time: Option<&'a str>,
+    /// Time for the departure query, defaults to now.
+    pub time: Option<DvbTime>,
     /// If true, time is interpreted as arrival time.
     pub isarrival: Option<bool>,
     /// Include short-term changes.
@@ -80,6 +135,33 @@ pub struct Params<'a> {
     pub mot: Option<&'a [Mot]>,
 }
 
+impl<'a> Params<'a> {
+    /// Query departures at `time` instead of now.
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::{DvbTime, monitor::Params};
+    /// let params = Params {
+    ///     stopid: "33000028",
+    ///     ..Default::default()
+    /// }
+    /// .departing_at(DvbTime::in_n_minutes(30));
+    /// assert_eq!(params.isarrival, Some(false));
+    /// ```
+    pub fn departing_at(mut self, time: impl Into<DvbTime>) -> Self {
+        self.time = Some(time.into());
+        self.isarrival = Some(false);
+        self
+    }
+
+    /// Query arrivals at `time` instead of departures.
+    pub fn arriving_at(mut self, time: impl Into<DvbTime>) -> Self {
+        self.time = Some(time.into());
+        self.isarrival = Some(true);
+        self
+    }
+}
+
 /// Fetches upcoming departures from a specified stop using the VVO WebAPI.
 ///
 /// # Arguments
@@ -90,13 +172,131 @@ pub struct Params<'a> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/dm`
 pub async fn departure_monitor<'a>(params: Params<'a>) -> Result<DvbResponse<DepartureMonitor>> {
-    let result = reqwest::Client::new()
-        .post(MONITOR_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(result)
+    default_client().departure_monitor(params).await
+}
+
+/// Fetches the departures of several stops concurrently and merges them into one list.
+///
+/// Useful for interchanges like Postplatz that consist of several stop IDs.
+/// `params` is used for every stop, only its `stopid` is replaced.
+/// Departures reported by more than one stop are listed once, for the first stop reporting them.
+///
+/// The result is all or nothing: if the monitor of any stop fails, e.g. because of an invalid
+/// stop ID, the whole call fails with that error and no departures are returned.
+/// Call [`departure_monitor`] per stop to keep the departures of the others.
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> dvb::Result<()> {
+/// use dvb::monitor::{Params, aggregated_departure_monitor};
+///
+/// let departures = aggregated_departure_monitor(&["33000037", "33000036"], Params::default()).await?;
+/// for aggregated in &departures {
+///     println!("{} from {}", aggregated.departure.line_name, aggregated.stopid);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn aggregated_departure_monitor<'a>(
+    stopids: &[&'a str],
+    params: Params<'a>,
+) -> Result<Vec<AggregatedDeparture>> {
+    default_client()
+        .aggregated_departure_monitor(stopids, params)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn departure(scheduled_time: &str, real_time: Option<&str>) -> Departure {
+        serde_json::from_value(serde_json::json!({
+            "Id": "voe:11003: :H:j25",
+            "LineName": "3",
+            "Direction": "Wilder Mann",
+            "Mot": "Tram",
+            "ScheduledTime": scheduled_time,
+            "RealTime": real_time,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn delay_and_expected_time() {
+        let late = departure(
+            "/Date(1753008480000+0200)/",
+            Some("/Date(1753008660000+0200)/"),
+        );
+        assert_eq!(late.delay(), chrono::Duration::minutes(3));
+
+        let early = departure(
+            "/Date(1753008480000+0200)/",
+            Some("/Date(1753008420000+0200)/"),
+        );
+        assert_eq!(early.delay(), chrono::Duration::minutes(-1));
+
+        let unknown = departure("/Date(1753008480000+0200)/", None);
+        assert_eq!(unknown.delay(), chrono::Duration::zero());
+        assert_eq!(
+            unknown.expected_time().map(|t| t.timestamp()),
+            Some(1753008480)
+        );
+
+        let now = DvbTime::from_str("/Date(1753008000000+0200)/").unwrap();
+        assert_eq!(late.minutes_until(&now), Some(11));
+        assert_eq!(unknown.minutes_until(&now), Some(8));
+    }
+
+    #[test]
+    #[cfg(not(feature = "iso8601-serialization"))]
+    fn serialize_typed_time() {
+        let time = DvbTime::from_str("/Date(1753008480000+0200)/").unwrap();
+        let params = Params {
+            stopid: "33000028",
+            ..Default::default()
+        }
+        .arriving_at(time);
+
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json["time"], "/Date(1753008480000+0200)/");
+        assert_eq!(json["isarrival"], true);
+    }
+
+    #[test]
+    fn aggregate_dedups_and_sorts() {
+        let mut first = departure("/Date(1753008480000+0200)/", None);
+        first.id = "a".into();
+        let mut second = departure("/Date(1753008300000+0200)/", None);
+        second.id = "b".into();
+        let mut delayed = departure(
+            "/Date(1753008000000+0200)/",
+            Some("/Date(1753008600000+0200)/"),
+        );
+        delayed.id = "c".into();
+
+        let postplatz = DepartureMonitor {
+            name: Some("Postplatz".into()),
+            place: None,
+            departures: Some(vec![first.clone(), delayed]),
+        };
+        let wilsdruffer = DepartureMonitor {
+            name: Some("Postplatz (Wilsdruffer Straße)".into()),
+            place: None,
+            departures: Some(vec![second, first]),
+        };
+
+        let merged = aggregate([("33000037", postplatz), ("33000036", wilsdruffer)]);
+        let merged: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|a| (a.departure.id.as_str(), a.stopid.as_str()))
+            .collect();
+
+        assert_eq!(
+            merged,
+            [("b", "33000036"), ("a", "33000037"), ("c", "33000037")]
+        );
+    }
 }
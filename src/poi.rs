@@ -20,6 +20,7 @@ pub struct PoiId {
     pub r#type: PoiType,
 }
 
+/// Ids without a known prefix are treated as stops, so parsing currently never fails.
 impl FromStr for PoiId {
     type Err = ();
 
@@ -1,40 +1,114 @@
 //! Types and utilities for querying stops and points.
 
-use schemars::JsonSchema;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{
     Deserialize, Serialize,
     de::{self, Deserializer, Visitor},
 };
-use serde_json::Value;
 
-use std::{error::Error, fmt, result, str::FromStr};
+use std::{borrow::Cow, error::Error, fmt, result, str::FromStr};
 
 use crate::{
     DvbResponse,
+    client::default_client,
+    coords::{Coordinate, Wgs84},
     error::Result,
     poi::{PoiId, PoiType},
 };
 
-#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Point {
     pub id: String,
     pub city: String,
     pub name: String,
-    pub coords: (i64, i64),
+    pub coords: Coordinate,
     pub r#type: PoiType,
 }
 
+/// Points are read from PointFinder rows like `"33000028||Dresden|Hauptbahnhof|5657516|4621644|0||"`,
+/// but written as objects.
+impl JsonSchema for Point {
+    fn schema_name() -> Cow<'static, str> {
+        "Point".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        if generator.contract().is_serialize() {
+            return json_schema!({
+                "description": "A stop, address or POI as written by this crate.",
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "city": { "type": "string" },
+                    "name": { "type": "string" },
+                    "coords": generator.subschema_for::<Coordinate>(),
+                    "type": generator.subschema_for::<PoiType>(),
+                },
+                "required": ["id", "city", "name", "coords", "type"],
+            });
+        }
+        json_schema!({
+            "description": "A PointFinder row: id, (unused), city, name, GK4 northing and easting, separated by `|`.",
+            "type": "string",
+            "pattern": "^[^|]+\\|[^|]*\\|[^|]*\\|[^|]*\\|-?[0-9]+\\|-?[0-9]+(\\|.*)?$",
+        })
+    }
+}
+
+/// Why a PointFinder row could not be parsed into a [`Point`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PointParseError {
+    /// The row ended before the named field.
+    MissingField(&'static str),
+    /// The named coordinate field is not an integer.
+    InvalidCoordinate { field: &'static str, value: String },
+}
+
+impl fmt::Display for PointParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointParseError::MissingField(field) => write!(f, "missing field {field}"),
+            PointParseError::InvalidCoordinate { field, value } => {
+                write!(f, "invalid {field} {value:?}")
+            }
+        }
+    }
+}
+
+impl Error for PointParseError {}
+
 impl FromStr for Point {
-    type Err = Box<dyn Error>;
+    type Err = PointParseError;
 
     fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split('|').collect();
+        let field = |index: usize, name: &'static str| {
+            parts
+                .get(index)
+                .copied()
+                .ok_or(PointParseError::MissingField(name))
+        };
+        let coordinate = |index: usize, name: &'static str| {
+            let value = field(index, name)?;
+            value
+                .parse::<i64>()
+                .map_err(|_| PointParseError::InvalidCoordinate {
+                    field: name,
+                    value: value.into(),
+                })
+        };
+
+        let id = field(0, "id")?;
+        if id.is_empty() {
+            return Err(PointParseError::MissingField("id"));
+        }
+
         let point = Point {
-            id: parts[0].into(),
-            city: parts[2].into(),
-            name: parts[3].into(),
-            coords: (parts[4].parse()?, parts[5].parse()?),
-            r#type: PoiId::from_str(parts[0]).unwrap().r#type,
+            id: id.into(),
+            city: field(2, "city")?.into(),
+            name: field(3, "name")?.into(),
+            coords: Coordinate::new(coordinate(4, "northing")?, coordinate(5, "easting")?),
+            r#type: PoiId::from_str(id).map_or(PoiType::Stop, |poi_id| poi_id.r#type),
         };
 
         Ok(point)
@@ -47,10 +121,10 @@ impl fmt::Display for Point {
             id,
             city,
             name,
-            coords: (lon, lat),
+            coords: Coordinate { northing, easting },
             ..
         } = self;
-        write!(f, "{id}||{city}|{name}|{lon}|{lat}|0||")
+        write!(f, "{id}||{city}|{name}|{northing}|{easting}|0||")
     }
 }
 
@@ -70,7 +144,7 @@ impl<'de> Visitor<'de> for PointVisitor {
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "a string that follows that \"id||city|name||lon|lat|0||\" format "
+            "a string that follows that \"id||city|name|northing|easting|0||\" format "
         )
     }
 
@@ -78,29 +152,162 @@ impl<'de> Visitor<'de> for PointVisitor {
     where
         E: de::Error,
     {
-        match Point::from_str(s) {
-            Ok(p) => Ok(p),
-            Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(s), &self)),
-        }
+        Point::from_str(s).map_err(|error| de::Error::custom(format!("{error} in point {s:?}")))
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Serialize, JsonSchema)]
 pub enum Format {
     #[default]
     Json,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// A PointFinder row that was skipped in lenient mode.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct RejectedPoint {
+    /// The row as sent by the service.
+    pub raw: String,
+    #[serde(serialize_with = "serialize_display")]
+    #[schemars(with = "String")]
+    pub error: PointParseError,
+}
+
+fn serialize_display<S: serde::Serializer>(
+    error: &PointParseError,
+    serializer: S,
+) -> result::Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
+}
+
+/// How well the PointFinder could resolve the query.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PointStatus {
+    /// The query matched exactly one point, which can be selected automatically.
+    Identified,
+    /// The query matched several points to choose from.
+    List,
+    /// Nothing matched the query.
+    NotIdentified,
+    /// A status this crate doesn't know yet.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Found {
-    pub point_status: String,
+    pub point_status: PointStatus,
     pub points: Vec<Point>,
+    /// Rows that could not be parsed, only filled in lenient mode (see [`Params::lenient`]).
+    #[serde(skip)]
+    pub rejected: Vec<RejectedPoint>,
+}
+
+impl Found {
+    /// Returns the matched point if the query was [`PointStatus::Identified`].
+    ///
+    /// Useful to select a stop automatically instead of showing a picker.
+    pub fn identified(&self) -> Option<&Point> {
+        match self.point_status {
+            PointStatus::Identified => self.points.first(),
+            _ => None,
+        }
+    }
+
+    /// Parses a PointFinder response, skipping malformed rows instead of failing.
+    ///
+    /// This is what [`Params::lenient`] does for requests, for responses obtained elsewhere,
+    /// e.g. from a cache. Skipped rows end up in [`Found::rejected`].
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::point::Found;
+    /// let found = Found::from_json_lenient(
+    ///     r#"{"PointStatus":"List","Points":["33000028|||Hauptbahnhof|5657516|4621644|0||","broken"]}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(found.points.len(), 1);
+    /// assert_eq!(found.rejected[0].raw, "broken");
+    /// ```
+    pub fn from_json_lenient(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str::<RawFound>(json).map(Found::from)
+    }
+}
+
+/// [`Found`] with unparsed rows, used in lenient mode.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct RawFound {
+    point_status: PointStatus,
+    #[serde(default)]
+    points: Vec<String>,
+}
+
+impl From<RawFound> for Found {
+    fn from(raw: RawFound) -> Self {
+        let mut points = Vec::with_capacity(raw.points.len());
+        let mut rejected = Vec::new();
+        for row in raw.points {
+            match Point::from_str(&row) {
+                Ok(point) => points.push(point),
+                Err(error) => rejected.push(RejectedPoint { raw: row, error }),
+            }
+        }
+        Found {
+            point_status: raw.point_status,
+            points,
+            rejected,
+        }
+    }
 }
 
-const POINT_FINDER_URL: &str = "https://webapi.vvo-online.de/tr/pointfinder";
+/// A point found near a position, see [`crate::find_stops_near`].
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct NearbyPoint {
+    #[serde(flatten)]
+    pub point: Point,
+    /// Distance from the searched position in metres.
+    pub distance: f64,
+}
+
+/// Points found near a position, closest first.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct Nearby {
+    pub points: Vec<NearbyPoint>,
+}
+
+impl Nearby {
+    /// Keeps the points of `found` within `radius` metres of `origin`, sorted by distance.
+    ///
+    /// Only stops are kept: addresses, POIs, the coordinate point the service echoes
+    /// for the query itself and points without a known position are skipped.
+    pub fn new(found: Found, origin: Wgs84, radius: f64) -> Self {
+        let mut points: Vec<NearbyPoint> = found
+            .points
+            .into_iter()
+            .filter(|point| point.r#type == PoiType::Stop)
+            .filter_map(|point| {
+                let distance = point.coords.to_wgs84()?.distance_to(&origin);
+                Some(NearbyPoint { point, distance })
+            })
+            .filter(|nearby| nearby.distance <= radius)
+            .collect();
+        points.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        Nearby { points }
+    }
+}
+
+/// Builds a PointFinder query for a WGS84 position, e.g. `coord:4621644:5657516`.
+pub(crate) fn coord_query(position: Wgs84) -> String {
+    let Coordinate { northing, easting } = Coordinate::from_wgs84(position);
+    format!("coord:{easting}:{northing}")
+}
 
-#[derive(Serialize, Debug)]
+pub(crate) const POINT_FINDER_PATH: &str = "/tr/pointfinder";
+
+#[derive(Serialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Params<'a> {
     pub query: &'a str,
@@ -118,6 +325,11 @@ pub struct Params<'a> {
     /// Include line information in results.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub showlines: Option<bool>,
+    /// Skip malformed rows instead of failing the whole response.
+    ///
+    /// Skipped rows are reported in [`Found::rejected`]. Not sent to the service.
+    #[serde(skip)]
+    pub lenient: bool,
 }
 
 impl<'a> Default for Params<'a> {
@@ -132,20 +344,13 @@ impl<'a> Default for Params<'a> {
             regional_only: None,
             stop_shortcuts: None,
             showlines: None,
+            lenient: false,
         }
     }
 }
 
 pub async fn point_finder<'a>(config: &Params<'a>) -> Result<DvbResponse<Found>> {
-    let response: Value = reqwest::Client::new()
-        .post(POINT_FINDER_URL)
-        .json(&config)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(serde_json::from_value(response)?)
+    default_client().point_finder(config).await
 }
 
 #[cfg(test)]
@@ -162,6 +367,124 @@ mod tests {
         assert_eq!(point.city, "Wroclaw");
     }
 
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Point::from_str("33000028|||Hauptbahnhof").unwrap_err(),
+            PointParseError::MissingField("northing")
+        );
+        assert_eq!(
+            Point::from_str("|||Hauptbahnhof|5657516|4621644|0||").unwrap_err(),
+            PointParseError::MissingField("id")
+        );
+        assert_eq!(
+            Point::from_str("33000028|||Hauptbahnhof|5657516|46x1644|0||").unwrap_err(),
+            PointParseError::InvalidCoordinate {
+                field: "easting",
+                value: "46x1644".into()
+            }
+        );
+    }
+
+    #[test]
+    fn strict_and_lenient_found() {
+        let json = r#"{
+            "PointStatus": "List",
+            "Points": [
+                "33000028|||Hauptbahnhof|5657516|4621644|0||",
+                "33000036|||Hauptbahnhof (Friedrich-List-Platz)",
+                "33000032|||Hauptbahnhof Nord|5657679|4621791|0||"
+            ]
+        }"#;
+
+        let strict = serde_json::from_str::<Found>(json).unwrap_err();
+        assert!(strict.to_string().contains("missing field northing"));
+
+        let lenient = Found::from_json_lenient(json).unwrap();
+        assert_eq!(lenient.points.len(), 2);
+        assert_eq!(lenient.rejected.len(), 1);
+        assert_eq!(
+            lenient.rejected[0].error,
+            PointParseError::MissingField("northing")
+        );
+    }
+
+    #[test]
+    fn serialize_schema_matches_output() {
+        let point = Point::from_str("33000028|||Hauptbahnhof|5657516|4621644|0||").unwrap();
+        let value = serde_json::to_value(&point).unwrap();
+        let schema = schemars::generate::SchemaSettings::default()
+            .for_serialize()
+            .into_generator()
+            .into_root_schema_for::<Point>();
+
+        let mut fields: Vec<&String> = value.as_object().unwrap().keys().collect();
+        let mut properties: Vec<&String> = schema
+            .get("properties")
+            .and_then(|properties| properties.as_object())
+            .unwrap()
+            .keys()
+            .collect();
+        fields.sort();
+        properties.sort();
+        assert_eq!(fields, properties);
+        assert!(value["coords"]["Latitude"].is_i64());
+        assert!(value["type"].is_string());
+    }
+
+    #[test]
+    fn point_status() {
+        let found: Found = serde_json::from_str(
+            r#"{"PointStatus":"Identified","Points":["33000028|||Hauptbahnhof|5657516|4621644|0||"]}"#,
+        )
+        .unwrap();
+        assert_eq!(found.identified().map(|p| p.id.as_str()), Some("33000028"));
+
+        let found: Found = serde_json::from_str(
+            r#"{"PointStatus":"List","Points":["33000028|||Hauptbahnhof|5657516|4621644|0||"]}"#,
+        )
+        .unwrap();
+        assert!(found.identified().is_none());
+
+        let found: Found =
+            serde_json::from_str(r#"{"PointStatus":"SomethingNew","Points":[]}"#).unwrap();
+        assert_eq!(found.point_status, PointStatus::Unknown);
+    }
+
+    #[test]
+    fn nearby_sorted_by_distance() {
+        let found = Found {
+            rejected: Vec::new(),
+            point_status: PointStatus::List,
+            points: [
+                "coord:4621644:5657516:GK4:0|||Wiener Platz|5657516|4621644|0||",
+                "33000036|||Hauptbahnhof (Friedrich-List-Platz)|5657383|4621726|0||",
+                "poiID:1234:14612000:-1:Hbf Dresden|||Kiosk|5657520|4621650|0||",
+                "streetID:1500:14:14612000:-1|||Wiener Platz 4|5657500|4621640|0||",
+                "33000028|||Hauptbahnhof|5657516|4621644|0||",
+                "33000032|||Hauptbahnhof Nord|5657679|4621791|0||",
+                "33000037|||Postplatz|5659353|4622195|0||",
+                "9022501||Hausen im Wiesental|Bahnhof|0|0|0||",
+            ]
+            .iter()
+            .map(|p| Point::from_str(p).unwrap())
+            .collect(),
+        };
+        let origin = Coordinate::new(5657516, 4621644).to_wgs84().unwrap();
+
+        let nearby = Nearby::new(found, origin, 500.0);
+        let ids: Vec<&str> = nearby.points.iter().map(|p| p.point.id.as_str()).collect();
+
+        assert_eq!(ids, ["33000028", "33000036", "33000032"]);
+        assert!(nearby.points[0].distance < 1.0);
+    }
+
+    #[test]
+    fn coord_query_uses_gk4() {
+        let position = Coordinate::new(5657516, 4621644).to_wgs84().unwrap();
+        assert_eq!(coord_query(position), "coord:4621644:5657516");
+    }
+
     #[test]
     fn from_string() {
         let points = [
@@ -0,0 +1,199 @@
+//! Fares as reported by the route planner, e.g. `"2,70"`.
+
+use std::{borrow::Cow, error::Error, fmt, str::FromStr};
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+};
+
+/// An amount of money in euro cents.
+///
+/// Parsed from and serialized to the German notation used by the API.
+///
+/// # Example
+/// ```
+/// # use dvb::Price;
+/// let price: Price = "2,70".parse().unwrap();
+/// assert_eq!(price.cents(), 270);
+/// assert_eq!(price.to_string(), "2,70 €");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Price(u32);
+
+impl Price {
+    pub fn from_cents(cents: u32) -> Self {
+        Self(cents)
+    }
+
+    pub fn cents(&self) -> u32 {
+        self.0
+    }
+
+    /// The amount in the API's notation, without currency sign.
+    fn to_api_string(self) -> String {
+        format!("{},{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} €", self.to_api_string())
+    }
+}
+
+impl JsonSchema for Price {
+    fn schema_name() -> Cow<'static, str> {
+        "Price".into()
+    }
+
+    /// Written as `"2,70"`, read from that, `"2.70"`, `"2,70 €"` or a number of euros.
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        if generator.contract().is_serialize() {
+            return json_schema!({
+                "description": "An amount in euros in German notation, e.g. \"2,70\".",
+                "type": "string",
+                "pattern": "^[0-9]+,[0-9]{2}$",
+            });
+        }
+        json_schema!({
+            "description": "An amount in euros, e.g. \"2,70\", \"2.70\", \"2,70 €\" or 2.7.",
+            "anyOf": [
+                {
+                    "type": "string",
+                    "pattern": "^\\s*[0-9]+([,.][0-9]{1,2})?\\s*€*\\s*$",
+                },
+                {
+                    "type": "number",
+                    "minimum": 0,
+                },
+            ],
+        })
+    }
+}
+
+/// Returned when a string is not an amount like `"2,70"` or `"2.70"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceParseError(String);
+
+impl fmt::Display for PriceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid price {:?}", self.0)
+    }
+}
+
+impl Error for PriceParseError {}
+
+impl FromStr for Price {
+    type Err = PriceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PriceParseError(s.into());
+        let amount = s.trim().trim_end_matches('€').trim_end();
+        let (euros, cents) = amount.split_once([',', '.']).unwrap_or((amount, ""));
+
+        let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !digits(euros) || !(cents.is_empty() || digits(cents) && cents.len() <= 2) {
+            return Err(invalid());
+        }
+
+        let euros: u32 = euros.parse().map_err(|_| invalid())?;
+        let cents: u32 = match cents.len() {
+            0 => 0,
+            1 => cents.parse::<u32>().map_err(|_| invalid())? * 10,
+            _ => cents.parse().map_err(|_| invalid())?,
+        };
+        euros
+            .checked_mul(100)
+            .and_then(|euros| euros.checked_add(cents))
+            .map(Price)
+            .ok_or_else(invalid)
+    }
+}
+
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_api_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PriceVisitor)
+    }
+}
+
+struct PriceVisitor;
+
+impl<'de> Visitor<'de> for PriceVisitor {
+    type Value = Price;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an amount like \"2,70\"")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        s.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, euros: u64) -> Result<Self::Value, E> {
+        euros
+            .checked_mul(100)
+            .and_then(|cents| u32::try_from(cents).ok())
+            .map(Price)
+            .ok_or_else(|| E::custom("price out of range"))
+    }
+
+    fn visit_f64<E: de::Error>(self, euros: f64) -> Result<Self::Value, E> {
+        let cents = (euros * 100.0).round();
+        if (0.0..=f64::from(u32::MAX)).contains(&cents) {
+            Ok(Price(cents as u32))
+        } else {
+            Err(E::custom("price out of range"))
+        }
+    }
+}
+
+/// Deserializes an optional value that the API sends as a string, treating `""` as missing.
+pub(crate) fn deserialize_blank_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(serde_json::Value::String(s)) => s.parse().map(Some).map_err(de::Error::custom),
+        Some(other) => other
+            .to_string()
+            .parse()
+            .map(Some)
+            .map_err(de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_notations() {
+        for (input, cents) in [("2,70", 270), ("2.7", 270), ("12", 1200), (" 0,05 € ", 5)] {
+            assert_eq!(input.parse::<Price>(), Ok(Price::from_cents(cents)));
+        }
+        for input in ["", "2,705", "-1,00", "zwei", ",50"] {
+            assert!(input.parse::<Price>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let price: Price = serde_json::from_str("\"3,00\"").unwrap();
+        assert_eq!(serde_json::to_string(&price).unwrap(), "\"3,00\"");
+        let price: Price = serde_json::from_str("2.7").unwrap();
+        assert_eq!(price.cents(), 270);
+        assert!(serde_json::from_str::<Price>(&u64::MAX.to_string()).is_err());
+    }
+}
@@ -1,10 +1,28 @@
 //! Route planning and route details for Dresden public transport.
 
-use crate::{DvbResponse, common::ArrivalState, error::Result, time::DvbTime};
-use serde::{Deserialize, Serialize};
+use crate::{
+    DvbResponse,
+    client::default_client,
+    common::{ArrivalState, Mot, Occupancy},
+    coords::{Coordinate, Wgs84},
+    error::Result,
+    price::{Price, deserialize_blank_as_none},
+    time::DvbTime,
+};
+use futures_util::Stream;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::IntoDeserializer};
 use serde_json::Value;
+use std::{
+    collections::{HashSet, VecDeque},
+    error::Error,
+    fmt,
+    str::FromStr,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+pub use crate::common::{Diva, Platform};
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Route {
     pub duration: Option<u32>,
@@ -17,18 +35,61 @@ pub struct Route {
     pub map_pdf_id: Option<String>,
     pub mot_chain: Option<Vec<MotChain>>,
     pub net: Option<String>,
-    pub number_of_fare_zones: Option<String>,
-    pub number_of_fare_zones_day_ticket: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    #[schemars(with = "Option<String>")]
+    pub number_of_fare_zones: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    #[schemars(with = "Option<String>")]
+    pub number_of_fare_zones_day_ticket: Option<u32>,
     pub partial_routes: Option<Vec<PartialRoute>>,
-    pub price: Option<String>,
-    pub price_day_ticket: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    pub price: Option<Price>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    pub price_day_ticket: Option<Price>,
     pub price_level: Option<u32>,
     pub route_cancelled: Option<bool>,
     pub route_id: Option<u32>,
     pub tickets: Option<Vec<Ticket>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl Route {
+    /// All [`Route::map_data`] entries decoded, skipping malformed ones.
+    pub fn polylines(&self) -> Vec<Polyline> {
+        self.map_data
+            .iter()
+            .flatten()
+            .filter_map(|entry| entry.parse().ok())
+            .collect()
+    }
+
+    /// The cheapest of the offered tickets that has a price.
+    pub fn cheapest_ticket(&self) -> Option<&Ticket> {
+        self.tickets
+            .iter()
+            .flatten()
+            .filter(|ticket| ticket.price.is_some())
+            .min_by_key(|ticket| ticket.price)
+    }
+
+    fn stops(&self) -> impl DoubleEndedIterator<Item = &RegularStop> {
+        self.partial_routes
+            .iter()
+            .flatten()
+            .flat_map(|partial| partial.regular_stops.iter().flatten())
+    }
+
+    /// Departure at the first stop, the real-time prognosis if there is one.
+    pub fn departure_time(&self) -> Option<&DvbTime> {
+        self.stops().find_map(RegularStop::departure)
+    }
+
+    /// Arrival at the last stop, the real-time prognosis if there is one.
+    pub fn arrival_time(&self) -> Option<&DvbTime> {
+        self.stops().rev().find_map(RegularStop::arrival)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Routes {
     #[serde(default)]
@@ -36,7 +97,7 @@ pub struct Routes {
     pub session_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct MotChain {
     pub changes: Option<Vec<String>>,
@@ -49,22 +110,15 @@ pub struct MotChain {
     pub stateless_id: Option<String>,
     pub train_number: Option<String>,
     pub transportation_company: Option<String>,
-    pub r#type: Option<crate::common::Mot>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Diva {
-    pub network: Option<String>,
-    pub number: Option<String>,
+    pub r#type: Option<Mot>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct PartialRoute {
     pub duration: Option<u32>,
     pub map_data_index: Option<i32>,
-    pub mot: Option<Mot>,
+    pub mot: Option<MotChain>,
     pub next_departure_times: Option<Vec<DvbTime>>,
     pub partial_route_id: Option<u32>,
     pub previous_departure_times: Option<Vec<DvbTime>>,
@@ -76,24 +130,97 @@ pub struct PartialRoute {
     pub booking_link: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Mot {
-    #[serde(default)]
-    pub changes: Vec<String>,
-    pub direction: Option<String>,
-    pub diva: Option<Diva>,
-    pub dl_id: Option<String>,
-    pub name: Option<String>,
-    pub operator_code: Option<String>,
-    pub product_name: Option<String>,
-    pub stateless_id: Option<String>,
-    pub train_number: Option<String>,
-    pub transportation_company: Option<String>,
-    pub r#type: Option<String>,
+impl PartialRoute {
+    /// The mode of transport of this leg, e.g. [`Mot::Tram`] or [`Mot::Footpath`].
+    pub fn mode(&self) -> Option<Mot> {
+        self.mot.as_ref().and_then(|mot| mot.r#type)
+    }
+
+    /// The course of this leg, decoded from the [`Route::map_data`] entry it refers to.
+    ///
+    /// `None` if the leg has no map data or its entry is malformed.
+    pub fn geometry(&self, route: &Route) -> Option<Polyline> {
+        let index = usize::try_from(self.map_data_index?).ok()?;
+        route.map_data.as_ref()?.get(index)?.parse().ok()
+    }
+}
+
+/// The course of a part of a route, as drawn on a map.
+///
+/// Decoded from a [`Route::map_data`] entry like `"Tram|5657516|4621644|5657498|4621687|"`.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Polyline {
+    /// The mode of transport used along the line.
+    pub mode: Option<Mot>,
+    /// GK4 positions in travel order.
+    pub points: Vec<Coordinate>,
+}
+
+impl Polyline {
+    /// The points converted to WGS84, skipping unknown positions.
+    pub fn to_wgs84(&self) -> Vec<Wgs84> {
+        self.points
+            .iter()
+            .filter_map(Coordinate::to_wgs84)
+            .collect()
+    }
+}
+
+/// Returned when a [`Route::map_data`] entry can't be decoded into a [`Polyline`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapDataParseError {
+    /// A coordinate is not an integer.
+    InvalidCoordinate(String),
+    /// The entry ended after a northing without its easting.
+    MissingEasting,
+}
+
+impl fmt::Display for MapDataParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapDataParseError::InvalidCoordinate(value) => {
+                write!(f, "invalid coordinate {value:?}")
+            }
+            MapDataParseError::MissingEasting => write!(f, "missing easting"),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl Error for MapDataParseError {}
+
+impl FromStr for Polyline {
+    type Err = MapDataParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.split('|');
+        let mode = parts.next().filter(|mode| !mode.is_empty()).map(|mode| {
+            let mode: serde::de::value::StrDeserializer<'_, serde::de::value::Error> =
+                mode.into_deserializer();
+            Mot::deserialize(mode).unwrap_or(Mot::Unknown)
+        });
+
+        let values = parts
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse::<i64>()
+                    .map_err(|_| MapDataParseError::InvalidCoordinate(value.into()))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if values.len() % 2 != 0 {
+            return Err(MapDataParseError::MissingEasting);
+        }
+
+        let points = values
+            .chunks_exact(2)
+            .map(|pair| Coordinate::new(pair[0], pair[1]))
+            .collect();
+        Ok(Polyline { mode, points })
+    }
+}
+
+/// A stop served by a partial route, with its planned and real-time times.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct RegularStop {
     pub arrival_time: Option<DvbTime>,
@@ -105,11 +232,12 @@ pub struct RegularStop {
     pub departure_real_time: Option<DvbTime>,
     pub departure_state: Option<ArrivalState>,
     pub dh_id: Option<String>,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
+    /// Position of the stop, sent as `Latitude` and `Longitude` by the API.
+    #[serde(flatten)]
+    pub coords: Option<Coordinate>,
     pub map_pdf_id: Option<String>,
     pub name: Option<String>,
-    pub occupancy: Option<String>,
+    pub occupancy: Option<Occupancy>,
     #[serde(default)]
     pub park_and_rail: Vec<ParkAndRail>,
     pub place: Option<String>,
@@ -117,27 +245,39 @@ pub struct RegularStop {
     pub r#type: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Platform {
-    pub name: Option<String>,
-    pub r#type: Option<String>,
+impl RegularStop {
+    /// Returns the real-time departure prognosis, falling back to the scheduled time.
+    pub fn departure(&self) -> Option<&DvbTime> {
+        self.departure_real_time
+            .as_ref()
+            .or(self.departure_time.as_ref())
+    }
+
+    /// Returns the real-time arrival prognosis, falling back to the scheduled time.
+    pub fn arrival(&self) -> Option<&DvbTime> {
+        self.arrival_real_time
+            .as_ref()
+            .or(self.arrival_time.as_ref())
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Ticket {
     pub fare_zone_names: Option<String>,
     pub name: Option<String>,
-    pub number_of_fare_zones: Option<String>,
-    pub price: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    #[schemars(with = "Option<String>")]
+    pub number_of_fare_zones: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_blank_as_none")]
+    pub price: Option<Price>,
     pub price_level: Option<u32>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct ParkAndRail {
-    pub coordinates: Option<Coordinate>,
+    pub coordinates: Option<LatLng>,
     pub free_spaces: Option<u32>,
     pub name: Option<String>,
     #[serde(default)]
@@ -146,58 +286,210 @@ pub struct ParkAndRail {
     pub total_spaces: Option<u32>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
-pub struct Coordinate {
+pub struct LatLng {
     pub lat: Option<f64>,
     pub lng: Option<f64>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct ParkingLot {
-    pub coordinates: Coordinate,
+    pub coordinates: LatLng,
     #[serde(default)]
     pub occupied: bool,
     #[serde(default)]
     pub disabled_persons_only: bool,
 }
 
-const ROUTE_URL: &str = "https://webapi.vvo-online.de/tr/trips";
+pub(crate) const ROUTE_PATH: &str = "/tr/trips";
+pub(crate) const PREV_NEXT_PATH: &str = "/tr/prevnext";
+
+/// Accessibility level of a route query.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MobilityRestriction {
+    /// No restrictions.
+    #[default]
+    None,
+    /// Avoid stairs where possible.
+    Medium,
+    /// Step-free routes only.
+    High,
+    /// Use the individual settings of [`MobilitySettings`].
+    Individual,
+}
+
+/// How accessible the entrance of a vehicle has to be.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Entrance {
+    #[default]
+    Any,
+    SmallStep,
+    NoStep,
+}
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MobilitySettings {
-    /// Accessibility level: `"None"`, `"Medium"`, `"High"`, or `"Individual"`.
-    pub mobility_restriction: Option<String>,
-    /// Allow solid stairs (only with `"Individual"` restriction).
+    /// Accessibility level.
+    pub mobility_restriction: Option<MobilityRestriction>,
+    /// Allow solid stairs (only with [`MobilityRestriction::Individual`]).
     pub solid_stairs: Option<bool>,
-    /// Allow escalators (only with `"Individual"` restriction).
+    /// Allow escalators (only with [`MobilityRestriction::Individual`]).
     pub escalators: Option<bool>,
-    /// Prefer fewest changes (only with `"Individual"` restriction).
+    /// Prefer fewest changes (only with [`MobilityRestriction::Individual`]).
     pub least_change: Option<bool>,
-    /// Entrance requirement: `"Any"`, `"SmallStep"`, or `"NoStep"`.
-    pub entrance: Option<String>,
+    /// Entrance requirement.
+    pub entrance: Option<Entrance>,
+}
+
+impl MobilitySettings {
+    /// Settings without any preference, same as [`Default::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Settings for one of the predefined accessibility levels.
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::route::{Entrance, MobilityRestriction, MobilitySettings};
+    /// let settings = MobilitySettings::restricted(MobilityRestriction::High).entrance(Entrance::NoStep);
+    /// assert_eq!(settings.entrance, Some(Entrance::NoStep));
+    /// ```
+    pub fn restricted(restriction: MobilityRestriction) -> Self {
+        Self::new().mobility_restriction(restriction)
+    }
+
+    pub fn mobility_restriction(mut self, restriction: MobilityRestriction) -> Self {
+        self.mobility_restriction = Some(restriction);
+        self
+    }
+
+    /// Allow or avoid solid stairs, switches to [`MobilityRestriction::Individual`].
+    pub fn solid_stairs(mut self, allowed: bool) -> Self {
+        self.solid_stairs = Some(allowed);
+        self.mobility_restriction(MobilityRestriction::Individual)
+    }
+
+    /// Allow or avoid escalators, switches to [`MobilityRestriction::Individual`].
+    pub fn escalators(mut self, allowed: bool) -> Self {
+        self.escalators = Some(allowed);
+        self.mobility_restriction(MobilityRestriction::Individual)
+    }
+
+    /// Prefer routes with fewer changes, switches to [`MobilityRestriction::Individual`].
+    pub fn least_change(mut self, least_change: bool) -> Self {
+        self.least_change = Some(least_change);
+        self.mobility_restriction(MobilityRestriction::Individual)
+    }
+
+    pub fn entrance(mut self, entrance: Entrance) -> Self {
+        self.entrance = Some(entrance);
+        self
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Maximum number of changes of a route.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MaxChanges {
+    #[default]
+    Unlimited,
+    Two,
+    One,
+    None,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WalkingSpeed {
+    VerySlow,
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    VeryFast,
+}
+
+/// Whether routes may use services that cost extra.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ExtraCharge {
+    /// No filter, sent as an empty string.
+    #[default]
+    #[serde(rename = "")]
+    Any,
+    /// Only services without extra charge.
+    None,
+    /// Only local traffic.
+    LocalTraffic,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct StandardSettings {
     /// Modes of transport to include.
-    pub mot: Option<Vec<String>>,
-    /// Maximum transfers: `"Unlimited"`, `"Two"`, `"One"`, or `"None"`.
-    pub max_changes: Option<String>,
-    /// Walking speed: `"VerySlow"`, `"Slow"`, `"Normal"`, `"Fast"`, or `"VeryFast"`.
-    pub walking_speed: Option<String>,
+    pub mot: Option<Vec<Mot>>,
+    /// Maximum transfers.
+    pub max_changes: Option<MaxChanges>,
+    /// Walking speed.
+    pub walking_speed: Option<WalkingSpeed>,
     /// Maximum walking distance to a stop in minutes.
     pub footpath_to_stop: Option<u32>,
     /// Include nearby alternative stops.
     pub include_alternative_stops: Option<bool>,
-    /// Extra charge filter: `""`, `"None"`, or `"LocalTraffic"`.
-    pub extra_charge: Option<String>,
+    /// Extra charge filter.
+    pub extra_charge: Option<ExtraCharge>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl StandardSettings {
+    /// Settings without any preference, same as [`Default::default`].
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::{Mot, route::{MaxChanges, StandardSettings, WalkingSpeed}};
+    /// let settings = StandardSettings::new()
+    ///     .mot([Mot::Tram, Mot::Bus])
+    ///     .max_changes(MaxChanges::One)
+    ///     .walking_speed(WalkingSpeed::Slow);
+    /// assert_eq!(settings.mot.as_deref(), Some(&[Mot::Tram, Mot::Bus][..]));
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only use the given modes of transport.
+    pub fn mot(mut self, mot: impl IntoIterator<Item = Mot>) -> Self {
+        self.mot = Some(mot.into_iter().collect());
+        self
+    }
+
+    pub fn max_changes(mut self, max_changes: MaxChanges) -> Self {
+        self.max_changes = Some(max_changes);
+        self
+    }
+
+    pub fn walking_speed(mut self, walking_speed: WalkingSpeed) -> Self {
+        self.walking_speed = Some(walking_speed);
+        self
+    }
+
+    pub fn footpath_to_stop(mut self, minutes: u32) -> Self {
+        self.footpath_to_stop = Some(minutes);
+        self
+    }
+
+    pub fn include_alternative_stops(mut self, include: bool) -> Self {
+        self.include_alternative_stops = Some(include);
+        self
+    }
+
+    pub fn extra_charge(mut self, extra_charge: ExtraCharge) -> Self {
+        self.extra_charge = Some(extra_charge);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Params<'a> {
     /// Origin stop ID.
@@ -222,14 +514,255 @@ pub struct Params<'a> {
     pub standard_settings: Option<StandardSettings>,
 }
 
+impl<'a> Params<'a> {
+    /// Query routes from `origin` to `destination` departing at `time`.
+    ///
+    /// Short-term changes are included and the response is always requested as JSON.
+    ///
+    /// # Example
+    /// ```
+    /// # use dvb::{DvbTime, Mot, route::{MaxChanges, Params}};
+    /// let params = Params::new("33000028", "33000016", DvbTime::in_n_minutes(10))
+    ///     .arriving()
+    ///     .via("33000037")
+    ///     .max_changes(MaxChanges::One)
+    ///     .mot([Mot::Tram, Mot::Bus]);
+    /// assert!(params.isarrivaltime);
+    /// assert_eq!(params.format, "json");
+    /// ```
+    pub fn new(origin: &'a str, destination: &'a str, time: impl Into<DvbTime>) -> Self {
+        Self {
+            origin,
+            destination,
+            time: time.into(),
+            isarrivaltime: false,
+            shorttermchanges: true,
+            format: "json",
+            via: None,
+            mobility_settings: None,
+            standard_settings: None,
+        }
+    }
+
+    /// Interpret the time as latest arrival instead of earliest departure.
+    pub fn arriving(mut self) -> Self {
+        self.isarrivaltime = true;
+        self
+    }
+
+    /// Interpret the time as earliest departure, the default.
+    pub fn departing(mut self) -> Self {
+        self.isarrivaltime = false;
+        self
+    }
+
+    pub fn shorttermchanges(mut self, include: bool) -> Self {
+        self.shorttermchanges = include;
+        self
+    }
+
+    /// Route via the given stop ID.
+    pub fn via(mut self, stopid: &'a str) -> Self {
+        self.via = Some(stopid);
+        self
+    }
+
+    /// Set accessibility preferences.
+    pub fn accessibility(mut self, settings: MobilitySettings) -> Self {
+        self.mobility_settings = Some(settings);
+        self
+    }
+
+    /// Replace all journey preferences at once.
+    pub fn standard_settings(mut self, settings: StandardSettings) -> Self {
+        self.standard_settings = Some(settings);
+        self
+    }
+
+    pub fn max_changes(self, max_changes: MaxChanges) -> Self {
+        self.update_standard_settings(|settings| settings.max_changes(max_changes))
+    }
+
+    pub fn walking_speed(self, walking_speed: WalkingSpeed) -> Self {
+        self.update_standard_settings(|settings| settings.walking_speed(walking_speed))
+    }
+
+    /// Only use the given modes of transport.
+    pub fn mot(self, mot: impl IntoIterator<Item = Mot>) -> Self {
+        self.update_standard_settings(|settings| settings.mot(mot))
+    }
+
+    /// The same query, moved to just before the first or after the last of `routes`.
+    ///
+    /// Used for paging when the service didn't hand out a session.
+    pub(crate) fn shifted(&self, routes: &Routes, previous: bool) -> Option<Self> {
+        let minute = chrono::Duration::minutes(1);
+        let shifted = if previous {
+            let arrival = routes
+                .routes
+                .iter()
+                .filter_map(Route::arrival_time)
+                .min_by_key(|time| time.timestamp())?;
+            Self {
+                time: (**arrival - minute).into(),
+                ..self.clone()
+            }
+            .arriving()
+        } else {
+            let departure = routes
+                .routes
+                .iter()
+                .filter_map(Route::departure_time)
+                .max_by_key(|time| time.timestamp())?;
+            Self {
+                time: (**departure + minute).into(),
+                ..self.clone()
+            }
+            .departing()
+        };
+        Some(shifted)
+    }
+
+    fn update_standard_settings(
+        mut self,
+        update: impl FnOnce(StandardSettings) -> StandardSettings,
+    ) -> Self {
+        self.standard_settings = Some(update(self.standard_settings.unwrap_or_default()));
+        self
+    }
+}
+
+/// Request for the connections before or after a previous result of the same query.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PageParams<'a> {
+    #[serde(flatten)]
+    pub params: &'a Params<'a>,
+    pub session_id: &'a str,
+    pub previous: bool,
+}
+
+/// The request for the connections before or after a previous result, see [`earlier_routes`].
+pub(crate) enum PageRequest<'a> {
+    /// Continues the session of the previous result at [`PREV_NEXT_PATH`].
+    Session(PageParams<'a>),
+    /// Repeats the query at [`ROUTE_PATH`], moved past the previous result.
+    Shifted(Params<'a>),
+}
+
+impl<'a> PageRequest<'a> {
+    pub fn new(params: &'a Params<'a>, routes: &'a Routes, previous: bool) -> Result<Self> {
+        match routes.session_id.as_deref() {
+            Some(session_id) => Ok(PageRequest::Session(PageParams {
+                params,
+                session_id,
+                previous,
+            })),
+            None => params
+                .shifted(routes, previous)
+                .map(PageRequest::Shifted)
+                .ok_or(crate::error::Error::NoPagingReference),
+        }
+    }
+}
+
+/// Bookkeeping of [`routes_until`]: which routes were already yielded, which are still
+/// to be yielded, what to page from and when to stop.
+pub(crate) struct RoutePager {
+    deadline: i64,
+    seen: HashSet<RouteKey>,
+    pending: VecDeque<Route>,
+    last: Option<Routes>,
+    pub done: bool,
+}
+
+/// Identifies a connection across batches: its times and the lines it uses.
+type RouteKey = (
+    i64,
+    Option<i64>,
+    Vec<(Option<Mot>, Option<String>, Option<String>)>,
+);
+
+fn route_key(route: &Route, departure: i64) -> RouteKey {
+    let chain = route
+        .mot_chain
+        .iter()
+        .flatten()
+        .map(|mot| (mot.r#type, mot.name.clone(), mot.direction.clone()))
+        .collect();
+    (
+        departure,
+        route.arrival_time().map(|time| time.timestamp()),
+        chain,
+    )
+}
+
+impl RoutePager {
+    pub fn new(deadline: &DvbTime) -> Self {
+        Self {
+            deadline: deadline.timestamp(),
+            seen: HashSet::new(),
+            pending: VecDeque::new(),
+            last: None,
+            done: false,
+        }
+    }
+
+    /// Takes the next route of the batches fetched so far.
+    pub fn next_route(&mut self) -> Option<Route> {
+        self.pending.pop_front()
+    }
+
+    /// The batch to page from, `None` before the first request.
+    pub fn last(&self) -> Option<&Routes> {
+        self.last.as_ref()
+    }
+
+    /// Takes in the response for the next batch and returns the next route to yield.
+    ///
+    /// Errors are passed on and end the paging.
+    pub fn receive(&mut self, response: Result<DvbResponse<Routes>>) -> Option<Result<Route>> {
+        match response {
+            Ok(response) => {
+                let routes = response.into_inner();
+                let accepted = self.accept(&routes);
+                self.pending.extend(accepted);
+                self.last = Some(routes);
+                self.next_route().map(Ok)
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+
+    /// Returns the routes of `routes` that are new and depart before the deadline.
+    ///
+    /// Marks the pager as done once the deadline is passed or nothing new turned up.
+    pub fn accept(&mut self, routes: &Routes) -> Vec<Route> {
+        let mut accepted = Vec::new();
+        for route in &routes.routes {
+            let Some(departure) = route.departure_time().map(|time| time.timestamp()) else {
+                continue;
+            };
+            if departure > self.deadline {
+                self.done = true;
+                continue;
+            }
+            if self.seen.insert(route_key(route, departure)) {
+                accepted.push(route.clone());
+            }
+        }
+        if accepted.is_empty() {
+            self.done = true;
+        }
+        accepted
+    }
+}
+
 pub async fn route_details_json<'a>(params: &Params<'a>) -> Result<Value> {
-    Ok(reqwest::Client::new()
-        .post(ROUTE_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?)
+    default_client().route_details_json(params).await
 }
 
 /// Queries possible routes between two stops using the VVO WebAPI.
@@ -242,13 +775,321 @@ pub async fn route_details_json<'a>(params: &Params<'a>) -> Result<Value> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/tr/trips`
 pub async fn route_details<'a>(params: &Params<'a>) -> Result<DvbResponse<Routes>> {
-    let routes = reqwest::Client::new()
-        .post(ROUTE_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(routes)
+    default_client().route_details(params).await
+}
+
+/// Fetches the connections just before `routes`, a previous result for the same `params`.
+///
+/// Uses [`Routes::session_id`] if the service handed one out, otherwise the times of `routes`.
+/// Fails with [`Error::NoPagingReference`](crate::error::Error::NoPagingReference) if `routes`
+/// has neither, e.g. because it is empty.
+///
+/// # Example
+/// ```no_run
+/// # async fn run() -> dvb::Result<()> {
+/// use dvb::{DvbTime, route::{Params, earlier_routes, route_details}};
+///
+/// let params = Params::new("33000028", "33000016", DvbTime::now());
+/// let routes = route_details(&params).await?;
+/// let earlier = earlier_routes(&params, &routes).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn earlier_routes<'a>(
+    params: &Params<'a>,
+    routes: &Routes,
+) -> Result<DvbResponse<Routes>> {
+    default_client().earlier_routes(params, routes).await
+}
+
+/// Fetches the connections just after `routes`, a previous result for the same `params`.
+///
+/// Uses [`Routes::session_id`] if the service handed one out, otherwise the times of `routes`.
+/// Fails with [`Error::NoPagingReference`](crate::error::Error::NoPagingReference) if `routes`
+/// has neither, e.g. because it is empty.
+pub async fn later_routes<'a>(params: &Params<'a>, routes: &Routes) -> Result<DvbResponse<Routes>> {
+    default_client().later_routes(params, routes).await
+}
+
+/// Yields all connections for `params` departing until `deadline`, fetching later batches as needed.
+///
+/// The stream ends after the first error.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use dvb::{DvbTime, route::{Params, routes_until}};
+///
+/// # async fn run() {
+/// let params = Params::new("33000028", "33000016", DvbTime::now());
+/// let mut routes = std::pin::pin!(routes_until(params, DvbTime::in_n_minutes(60)));
+/// while let Some(Ok(route)) = routes.next().await {
+///     println!("{:?} min", route.duration);
+/// }
+/// # }
+/// ```
+pub fn routes_until<'a>(
+    params: Params<'a>,
+    deadline: DvbTime,
+) -> impl Stream<Item = Result<Route>> + 'a {
+    default_client().routes_until(params, deadline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_route_mode() {
+        let partials: Vec<PartialRoute> = serde_json::from_value(serde_json::json!([
+            { "Mot": { "Type": "Footpath" } },
+            { "Mot": { "Type": "Tram", "Name": "3", "Changes": ["511"] } },
+            { "Mot": { "Type": "Hoverboard" } },
+            {},
+        ]))
+        .unwrap();
+
+        let modes: Vec<Option<Mot>> = partials.iter().map(PartialRoute::mode).collect();
+        assert_eq!(
+            modes,
+            [
+                Some(Mot::Footpath),
+                Some(Mot::Tram),
+                Some(Mot::Unknown),
+                None
+            ]
+        );
+        let trams = partials.iter().filter(|p| p.mode() == Some(Mot::Tram));
+        assert_eq!(trams.count(), 1);
+    }
+
+    fn route(departure: &str, arrival: &str) -> Route {
+        serde_json::from_value(serde_json::json!({
+            "PartialRoutes": [
+                { "RegularStops": [{ "DepartureTime": departure, "CancelReasons": [] }] },
+                { "RegularStops": [{ "ArrivalTime": arrival, "CancelReasons": [] }] },
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn routes(times: &[(&str, &str)]) -> Routes {
+        Routes {
+            routes: times.iter().map(|(dep, arr)| route(dep, arr)).collect(),
+            session_id: None,
+        }
+    }
+
+    #[test]
+    fn decode_map_data() {
+        let route: Route = serde_json::from_value(serde_json::json!({
+            "MapData": [
+                "Footpath|5657516|4621644|5657520|4621650|",
+                "Tram|5657520|4621650|5658012|4621101|5659127|4620837|",
+                "Tram|5657520|",
+            ],
+            "PartialRoutes": [
+                { "MapDataIndex": 0 },
+                { "MapDataIndex": 1 },
+                { "MapDataIndex": 2 },
+                {},
+            ]
+        }))
+        .unwrap();
+        let partials = route.partial_routes.as_ref().unwrap();
+
+        let footpath = partials[0].geometry(&route).unwrap();
+        assert_eq!(footpath.mode, Some(Mot::Footpath));
+        assert_eq!(
+            footpath.points,
+            [
+                Coordinate::new(5657516, 4621644),
+                Coordinate::new(5657520, 4621650)
+            ]
+        );
+
+        let tram = partials[1].geometry(&route).unwrap();
+        assert_eq!(tram.mode, Some(Mot::Tram));
+        assert_eq!(tram.to_wgs84().len(), 3);
+
+        assert!(partials[2].geometry(&route).is_none());
+        assert!(partials[3].geometry(&route).is_none());
+        assert_eq!(route.polylines().len(), 2);
+        assert_eq!(
+            "Tram|5657520|x|".parse::<Polyline>(),
+            Err(MapDataParseError::InvalidCoordinate("x".into()))
+        );
+    }
+
+    #[test]
+    fn prices_and_zones() {
+        let route: Route = serde_json::from_value(serde_json::json!({
+            "NumberOfFareZones": "1",
+            "NumberOfFareZonesDayTicket": "",
+            "Price": "2,70",
+            "PriceDayTicket": "7,70",
+            "Tickets": [
+                { "Name": "Einzelfahrt", "Price": "3,00", "NumberOfFareZones": "2" },
+                { "Name": "Kurzstrecke", "Price": "2,00", "NumberOfFareZones": "1" },
+                { "Name": "Abo" },
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(route.price, Some(Price::from_cents(270)));
+        assert_eq!(route.price_day_ticket, Some(Price::from_cents(770)));
+        assert_eq!(route.number_of_fare_zones, Some(1));
+        assert_eq!(route.number_of_fare_zones_day_ticket, None);
+
+        let cheapest = route.cheapest_ticket().unwrap();
+        assert_eq!(cheapest.name.as_deref(), Some("Kurzstrecke"));
+        assert_eq!(cheapest.number_of_fare_zones, Some(1));
+    }
+
+    #[test]
+    fn route_times() {
+        let route = route("/Date(1753008480000+0200)/", "/Date(1753009080000+0200)/");
+        assert_eq!(
+            route.departure_time().map(|t| t.timestamp()),
+            Some(1753008480)
+        );
+        assert_eq!(
+            route.arrival_time().map(|t| t.timestamp()),
+            Some(1753009080)
+        );
+    }
+
+    #[test]
+    fn shift_without_session() {
+        let batch = routes(&[
+            ("/Date(1753008480000+0200)/", "/Date(1753009080000+0200)/"),
+            ("/Date(1753009080000+0200)/", "/Date(1753009680000+0200)/"),
+        ]);
+        let params = Params::new("33000028", "33000016", DvbTime::now());
+
+        let later = params.shifted(&batch, false).unwrap();
+        assert_eq!(later.time.timestamp(), 1753009080 + 60);
+        assert!(!later.isarrivaltime);
+
+        let earlier = params.shifted(&batch, true).unwrap();
+        assert_eq!(earlier.time.timestamp(), 1753009080 - 60);
+        assert!(earlier.isarrivaltime);
+
+        assert!(params.shifted(&routes(&[]), false).is_none());
+    }
+
+    #[test]
+    fn page_request() {
+        let params = Params::new("33000028", "33000016", DvbTime::now());
+        let mut batch = routes(&[("/Date(1753008480000+0200)/", "/Date(1753009080000+0200)/")]);
+        assert!(matches!(
+            PageRequest::new(&params, &batch, false),
+            Ok(PageRequest::Shifted(shifted)) if !shifted.isarrivaltime
+        ));
+        assert!(matches!(
+            PageRequest::new(&params, &routes(&[]), false),
+            Err(crate::error::Error::NoPagingReference)
+        ));
+
+        batch.session_id = Some("367417461%3a8".into());
+        assert!(matches!(
+            PageRequest::new(&params, &batch, true),
+            Ok(PageRequest::Session(page)) if page.previous && page.session_id == "367417461%3a8"
+        ));
+    }
+
+    #[test]
+    fn pager_ends_after_error() {
+        let mut pager = RoutePager::new(&DvbTime::now());
+        let response = pager.receive(Err(crate::error::Error::NoPagingReference));
+        assert!(matches!(response, Some(Err(_))));
+        assert!(pager.done);
+        assert!(pager.next_route().is_none());
+    }
+
+    #[test]
+    fn pager_stops_at_deadline() {
+        let deadline = "/Date(1753009800000+0200)/".parse::<DvbTime>().unwrap();
+        let mut pager = RoutePager::new(&deadline);
+
+        let first = pager.accept(&routes(&[
+            ("/Date(1753008480000+0200)/", "/Date(1753009080000+0200)/"),
+            ("/Date(1753009080000+0200)/", "/Date(1753009680000+0200)/"),
+        ]));
+        assert_eq!(first.len(), 2);
+        assert!(!pager.done);
+
+        let second = pager.accept(&routes(&[
+            ("/Date(1753009080000+0200)/", "/Date(1753009680000+0200)/"),
+            ("/Date(1753009680000+0200)/", "/Date(1753010280000+0200)/"),
+            ("/Date(1753010280000+0200)/", "/Date(1753010880000+0200)/"),
+        ]));
+        assert_eq!(second.len(), 1);
+        assert!(pager.done);
+    }
+
+    #[test]
+    fn pager_keeps_equal_departures() {
+        let deadline = "/Date(1753012800000+0200)/".parse::<DvbTime>().unwrap();
+        let mut pager = RoutePager::new(&deadline);
+        let by_line = |line: &str| {
+            let mut route = route("/Date(1753008300000+0200)/", "/Date(1753009080000+0200)/");
+            route.mot_chain = serde_json::from_value(serde_json::json!([
+                { "Type": "Tram", "Name": line }
+            ]))
+            .unwrap();
+            route
+        };
+        let batch = |lines: &[&str]| Routes {
+            routes: lines.iter().map(|line| by_line(line)).collect(),
+            session_id: None,
+        };
+
+        assert_eq!(pager.accept(&batch(&["3"])).len(), 1);
+
+        let second = pager.accept(&batch(&["3", "7"]));
+        let lines: Vec<_> = second
+            .iter()
+            .flat_map(|route| route.mot_chain.iter().flatten())
+            .map(|mot| mot.name.as_deref())
+            .collect();
+        assert_eq!(lines, [Some("7")]);
+        assert!(!pager.done);
+    }
+
+    #[test]
+    fn page_params() {
+        let params = Params::new("33000028", "33000016", DvbTime::now());
+        let page = PageParams {
+            params: &params,
+            session_id: "367417461%3a8",
+            previous: true,
+        };
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["origin"], "33000028");
+        assert_eq!(json["sessionId"], "367417461%3a8");
+        assert_eq!(json["previous"], true);
+    }
+
+    #[test]
+    fn serialize_settings() {
+        let standard = StandardSettings::new()
+            .mot([Mot::Tram, Mot::SuburbanRailway])
+            .max_changes(MaxChanges::Two)
+            .walking_speed(WalkingSpeed::VeryFast)
+            .extra_charge(ExtraCharge::Any);
+        let json = serde_json::to_value(&standard).unwrap();
+        assert_eq!(json["mot"], serde_json::json!(["Tram", "SuburbanRailway"]));
+        assert_eq!(json["maxChanges"], "Two");
+        assert_eq!(json["walkingSpeed"], "VeryFast");
+        assert_eq!(json["extraCharge"], "");
+
+        let mobility = MobilitySettings::new()
+            .escalators(false)
+            .entrance(Entrance::SmallStep);
+        let json = serde_json::to_value(&mobility).unwrap();
+        assert_eq!(json["mobilityRestriction"], "Individual");
+        assert_eq!(json["escalators"], false);
+        assert_eq!(json["entrance"], "SmallStep");
+    }
 }
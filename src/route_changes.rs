@@ -3,10 +3,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{DvbResponse, common::Mot, error::Result, time::DvbTime};
+use crate::{
+    DvbResponse,
+    client::default_client,
+    common::{Diva, Mot},
+    error::Result,
+    time::DvbTime,
+};
 
-const ROUTE_CHANGES_URL: &str = "https://webapi.vvo-online.de/rc";
-const ROUTE_CHANGE_LINES_URL: &str = "https://webapi.vvo-online.de/rc/lines";
+pub(crate) const ROUTE_CHANGES_PATH: &str = "/rc";
+pub(crate) const ROUTE_CHANGE_LINES_PATH: &str = "/rc/lines";
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -40,13 +46,6 @@ pub struct Banner {
     pub trip_request_include: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Diva {
-    pub number: Option<String>,
-    pub network: Option<String>,
-}
-
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Line {
@@ -76,7 +75,7 @@ pub struct RouteChangeLines {
     pub lines: Vec<Line>,
 }
 
-#[derive(Serialize, Clone, Debug, Default)]
+#[derive(Serialize, JsonSchema, Clone, Debug, Default)]
 pub struct Params<'a> {
     /// Include short-term changes.
     pub shortterm: Option<bool>,
@@ -86,7 +85,7 @@ pub struct Params<'a> {
     pub format: Option<&'a str>,
 }
 
-#[derive(Serialize, Clone, Debug, Default)]
+#[derive(Serialize, JsonSchema, Clone, Debug, Default)]
 pub struct LinesParams<'a> {
     /// Provider filter.
     pub provider: Option<&'a str>,
@@ -104,15 +103,7 @@ pub struct LinesParams<'a> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/rc`
 pub async fn route_changes(params: Params<'_>) -> Result<DvbResponse<RouteChanges>> {
-    let response = reqwest::Client::new()
-        .post(ROUTE_CHANGES_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(response)
+    default_client().route_changes(params).await
 }
 
 /// Fetches lines affected by route changes from the VVO WebAPI.
@@ -125,13 +116,5 @@ pub async fn route_changes(params: Params<'_>) -> Result<DvbResponse<RouteChange
 ///
 /// Endpoint: `https://webapi.vvo-online.de/rc/lines`
 pub async fn route_change_lines(params: LinesParams<'_>) -> Result<DvbResponse<RouteChangeLines>> {
-    let response = reqwest::Client::new()
-        .post(ROUTE_CHANGE_LINES_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(response)
+    default_client().route_change_lines(params).await
 }
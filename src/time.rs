@@ -37,13 +37,28 @@ impl DvbTime {
     /// assert!(wait.ends_with("min"));
     /// ```
     pub fn wait(&self) -> String {
-        let now = Local::now();
-        let dt: DateTime<FixedOffset> = now.with_timezone(now.offset());
-        let min = self.0.sub(dt).num_minutes();
+        let min = self.wait_duration().num_minutes();
 
         format!("{min}min")
     }
 
+    /// Returns the time from now until this time, negative if it lies in the past.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dvb::DvbTime;
+    /// let future = DvbTime::in_n_minutes(10);
+    /// assert!(future.wait_duration() > chrono::Duration::minutes(9));
+    /// ```
+    pub fn wait_duration(&self) -> chrono::Duration {
+        self.duration_since(&DvbTime::now())
+    }
+
+    /// Returns the signed duration from `earlier` until this time.
+    pub fn duration_since(&self, earlier: &DvbTime) -> chrono::Duration {
+        self.0.sub(earlier.0)
+    }
+
     /// Returns the current local time as a `DvbTime`.
     pub fn now() -> Self {
         DvbTime::from(Local::now())
@@ -111,6 +126,12 @@ impl From<DateTime<Local>> for DvbTime {
     }
 }
 
+impl From<DateTime<Utc>> for DvbTime {
+    fn from(dt: DateTime<Utc>) -> Self {
+        DvbTime::from(dt.fixed_offset())
+    }
+}
+
 impl From<DateTime<FixedOffset>> for DvbTime {
     fn from(dt: DateTime<FixedOffset>) -> Self {
         DvbTime(dt)
@@ -129,21 +150,15 @@ impl FromStr for DvbTime {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = Regex::new(r"^/Date\((\d*)(\+|-)(\d{2})(\d{2})\)/")?;
         if let Some(caps) = re.captures(s) {
-            let raw_timestamp = &caps[1];
-            let timestamp: i64 = raw_timestamp.parse()?;
+            let timestamp_millis: i64 = caps[1].parse()?;
             let hours: i32 = caps[3].parse()?;
             let mins: i32 = caps[4].parse()?;
 
-            let multiplier = if raw_timestamp.ends_with("000") {
-                1000
-            } else {
-                1
-            };
-
             let fo = FixedOffset::east_opt(hours * 3600 + mins * 60)
-                .unwrap()
-                .timestamp_opt(timestamp / multiplier, 0)
-                .unwrap();
+                .ok_or("offset out of range")?
+                .timestamp_millis_opt(timestamp_millis)
+                .single()
+                .ok_or("timestamp out of range")?;
 
             Ok(DvbTime(fo))
         } else {
@@ -261,6 +276,13 @@ mod tests {
         println!("{parsed:?}");
     }
 
+    #[test]
+    fn sub_second_timestamp() {
+        let dt = DvbTime::from_str("/Date(1753008488015+0200)/").unwrap();
+        assert_eq!(dt.timestamp_millis(), 1753008488015);
+        assert_eq!(dt.offset().local_minus_utc(), 2 * 3600);
+    }
+
     #[test]
     fn deserialize_dvb_format() {
         let json = r#""/Date(1609459200000+0100)/""#;
@@ -3,14 +3,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{DvbResponse, common::ArrivalState, error::Result, time::DvbTime};
+use crate::{
+    DvbResponse,
+    client::default_client,
+    common::{ArrivalState, Occupancy},
+    coords::Coordinate,
+    error::Result,
+    time::DvbTime,
+};
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Platform {
-    name: String,
-    r#type: String, // enum PlatformType {Platform}
-}
+pub use crate::common::Platform;
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -22,6 +24,7 @@ pub enum Position {
     Onward,
 }
 
+/// A stop along a trip, with its scheduled and real-time arrival.
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Stop {
@@ -29,14 +32,29 @@ pub struct Stop {
     pub name: String,
     pub place: String,
     pub platform: Platform,
-    pub latitude: i64,
-    pub longitude: i64,
+    /// Position of the stop, sent as `Latitude` and `Longitude` by the API.
+    #[serde(flatten)]
+    pub coords: Coordinate,
     pub position: Position,
     pub scheduled_time: Option<bool>,
     pub time: DvbTime,
     pub real_time: Option<DvbTime>,
     pub state: Option<ArrivalState>,
-    pub occupancy: Option<String>,
+    pub occupancy: Option<Occupancy>,
+}
+
+impl Stop {
+    /// The northing of the stop, which the API calls `Latitude`.
+    #[deprecated(note = "use `coords.northing` instead")]
+    pub fn latitude(&self) -> i64 {
+        self.coords.northing
+    }
+
+    /// The easting of the stop, which the API calls `Longitude`.
+    #[deprecated(note = "use `coords.easting` instead")]
+    pub fn longitude(&self) -> i64 {
+        self.coords.easting
+    }
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
@@ -46,9 +64,9 @@ pub struct Trip {
     pub stops: Vec<Stop>,
 }
 
-const TRIP_URL: &str = "https://webapi.vvo-online.de/dm/trip";
+pub(crate) const TRIP_PATH: &str = "/dm/trip";
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Params<'a> {
     /// The trip ID to query.
@@ -71,13 +89,5 @@ pub struct Params<'a> {
 ///
 /// Endpoint: `https://webapi.vvo-online.de/dm/trip`
 pub async fn trip_details<'a>(params: &Params<'a>) -> Result<DvbResponse<Trip>> {
-    let response = reqwest::Client::new()
-        .post(TRIP_URL)
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    Ok(response)
+    default_client().trip_details(params).await
 }
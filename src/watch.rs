@@ -0,0 +1,291 @@
+//! Polling stream of departure changes for live departure boards.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use futures_util::{Stream, stream};
+
+use crate::{
+    DvbClient,
+    client::default_client,
+    common::{ArrivalState, Mot, Platform},
+    error::Result,
+    monitor::{self, Departure},
+    time::DvbTime,
+};
+
+/// Something that changed about a departure between two polls.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum DepartureEvent {
+    /// The departure showed up for the first time.
+    New(Departure),
+    /// The departure is no longer listed, usually because it has left.
+    Gone(Departure),
+    /// The real-time prognosis changed.
+    DelayChanged {
+        departure: Departure,
+        previous_real_time: Option<DvbTime>,
+    },
+    /// The departure moved to another platform.
+    PlatformChanged {
+        departure: Departure,
+        previous_platform: Option<Platform>,
+    },
+    /// The departure was cancelled.
+    Cancelled(Departure),
+}
+
+impl DepartureEvent {
+    /// The departure this event is about, in its most recent state.
+    pub fn departure(&self) -> &Departure {
+        match self {
+            DepartureEvent::New(departure)
+            | DepartureEvent::Gone(departure)
+            | DepartureEvent::DelayChanged { departure, .. }
+            | DepartureEvent::PlatformChanged { departure, .. }
+            | DepartureEvent::Cancelled(departure) => departure,
+        }
+    }
+}
+
+/// Configuration of [`watch_departures`].
+#[derive(Clone, Debug)]
+pub struct WatchOptions {
+    /// Minimum time between two polls.
+    ///
+    /// If a response expires later than that, the next poll waits for its `ExpirationTime`.
+    pub interval: Duration,
+    /// Maximum number of departures to request per poll.
+    pub limit: Option<u32>,
+    /// Filter by mode of transport.
+    pub mot: Option<Vec<Mot>>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            limit: Some(15),
+            mot: None,
+        }
+    }
+}
+
+/// Polls the departure monitor of a stop and yields what changed, keyed by [`Departure::id`].
+///
+/// The first poll yields a [`DepartureEvent::New`] for every listed departure.
+/// Failed polls yield an error and are retried after [`WatchOptions::interval`].
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use dvb::watch::{DepartureEvent, WatchOptions, watch_departures};
+///
+/// # async fn run() {
+/// let mut events = std::pin::pin!(watch_departures("33000028", WatchOptions::default()));
+/// while let Some(Ok(event)) = events.next().await {
+///     if let DepartureEvent::Cancelled(departure) = event {
+///         println!("{} to {} was cancelled", departure.line_name, departure.direction);
+///     }
+/// }
+/// # }
+/// ```
+pub fn watch_departures(
+    stopid: impl Into<String>,
+    options: WatchOptions,
+) -> impl Stream<Item = Result<DepartureEvent>> + Send + 'static {
+    default_client().watch_departures(stopid, options)
+}
+
+struct State {
+    client: DvbClient,
+    stopid: String,
+    options: WatchOptions,
+    known: Vec<Departure>,
+    pending: VecDeque<DepartureEvent>,
+    next_wait: Option<Duration>,
+}
+
+pub(crate) fn watch(
+    client: DvbClient,
+    stopid: String,
+    options: WatchOptions,
+) -> impl Stream<Item = Result<DepartureEvent>> + Send + 'static {
+    let state = State {
+        client,
+        stopid,
+        options,
+        known: Vec::new(),
+        pending: VecDeque::new(),
+        next_wait: None,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+            if let Some(wait) = state.next_wait.take() {
+                tokio::time::sleep(wait).await;
+            }
+
+            let params = monitor::Params {
+                stopid: &state.stopid,
+                limit: state.options.limit,
+                mot: state.options.mot.as_deref(),
+                ..Default::default()
+            };
+            match state.client.departure_monitor(params).await {
+                Ok(response) => {
+                    let ttl = response
+                        .time_to_live()
+                        .and_then(|ttl| ttl.to_std().ok())
+                        .unwrap_or_default();
+                    state.next_wait = Some(state.options.interval.max(ttl));
+                    let departures = response.into_inner().departures.unwrap_or_default();
+                    state.pending.extend(diff(&mut state.known, departures));
+                }
+                Err(error) => {
+                    state.next_wait = Some(state.options.interval);
+                    return Some((Err(error), state));
+                }
+            }
+        }
+    })
+}
+
+fn real_time(departure: &Departure) -> Option<i64> {
+    departure.real_time.as_ref().map(|t| t.timestamp())
+}
+
+fn is_cancelled(departure: &Departure) -> bool {
+    departure.state == Some(ArrivalState::Cancelled)
+}
+
+/// Replaces `known` with `current` and returns what changed.
+///
+/// [`DepartureEvent::Gone`] events come last, in the order of the previous poll.
+fn diff(known: &mut Vec<Departure>, current: Vec<Departure>) -> Vec<DepartureEvent> {
+    let previous = std::mem::replace(known, current);
+    let before: HashMap<&str, &Departure> = previous
+        .iter()
+        .map(|departure| (departure.id.as_str(), departure))
+        .collect();
+    let mut events = Vec::new();
+
+    for departure in known.iter() {
+        match before.get(departure.id.as_str()) {
+            None => events.push(DepartureEvent::New(departure.clone())),
+            Some(before) => {
+                if is_cancelled(departure) && !is_cancelled(before) {
+                    events.push(DepartureEvent::Cancelled(departure.clone()));
+                }
+                if departure.platform != before.platform {
+                    events.push(DepartureEvent::PlatformChanged {
+                        departure: departure.clone(),
+                        previous_platform: before.platform.clone(),
+                    });
+                }
+                if real_time(departure) != real_time(before) {
+                    events.push(DepartureEvent::DelayChanged {
+                        departure: departure.clone(),
+                        previous_real_time: before.real_time.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let current: HashSet<&str> = known
+        .iter()
+        .map(|departure| departure.id.as_str())
+        .collect();
+    events.extend(
+        previous
+            .into_iter()
+            .filter(|departure| !current.contains(departure.id.as_str()))
+            .map(DepartureEvent::Gone),
+    );
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn departure(id: &str, real_time: &str, platform: &str, state: &str) -> Departure {
+        serde_json::from_value(serde_json::json!({
+            "Id": id,
+            "LineName": "3",
+            "Direction": "Wilder Mann",
+            "Mot": "Tram",
+            "ScheduledTime": "/Date(1753008480000+0200)/",
+            "RealTime": real_time,
+            "State": state,
+            "Platform": { "Name": platform, "Type": "Platform" },
+        }))
+        .unwrap()
+    }
+
+    fn kinds(events: &[DepartureEvent]) -> Vec<(&str, &str)> {
+        events
+            .iter()
+            .map(|event| {
+                let kind = match event {
+                    DepartureEvent::New(_) => "new",
+                    DepartureEvent::Gone(_) => "gone",
+                    DepartureEvent::DelayChanged { .. } => "delay",
+                    DepartureEvent::PlatformChanged { .. } => "platform",
+                    DepartureEvent::Cancelled(_) => "cancelled",
+                };
+                (kind, event.departure().id.as_str())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_changes() {
+        let mut known = Vec::new();
+        let on_time = "/Date(1753008480000+0200)/";
+        let late = "/Date(1753008600000+0200)/";
+
+        let events = diff(
+            &mut known,
+            vec![
+                departure("a", on_time, "1", "InTime"),
+                departure("b", on_time, "2", "InTime"),
+                departure("c", on_time, "3", "InTime"),
+            ],
+        );
+        assert_eq!(kinds(&events), [("new", "a"), ("new", "b"), ("new", "c")]);
+
+        let events = diff(
+            &mut known,
+            vec![
+                departure("a", late, "1", "Delayed"),
+                departure("b", on_time, "4", "InTime"),
+                departure("c", on_time, "3", "Cancelled"),
+                departure("d", on_time, "1", "InTime"),
+            ],
+        );
+        assert_eq!(
+            kinds(&events),
+            [
+                ("delay", "a"),
+                ("platform", "b"),
+                ("cancelled", "c"),
+                ("new", "d")
+            ]
+        );
+
+        let events = diff(&mut known, vec![departure("d", on_time, "1", "InTime")]);
+        assert_eq!(
+            kinds(&events),
+            [("gone", "a"), ("gone", "b"), ("gone", "c")]
+        );
+        assert!(diff(&mut known, vec![departure("d", on_time, "1", "InTime")]).is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+//! Snapshots of the generated JSON schemas, so contract changes show up in review.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to accept changed schemas.
+
+use std::{fs, path::Path};
+
+use schemars::{JsonSchema, Schema, generate::SchemaSettings, schema_for};
+
+fn assert_snapshot<T: JsonSchema>(name: &str) {
+    assert_schema_snapshot(name, schema_for!(T));
+}
+
+/// Snapshot of the schema of what this crate writes, which differs from what it reads for some types.
+fn assert_serialize_snapshot<T: JsonSchema>(name: &str) {
+    let schema = SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    assert_schema_snapshot(name, schema);
+}
+
+fn assert_schema_snapshot(name: &str, schema: Schema) {
+    let schema = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.json"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, schema).unwrap();
+        return;
+    }
+    let snapshot = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("missing snapshot {}: {error}", path.display()));
+    assert!(
+        schema == snapshot,
+        "schema of {name} changed, rerun with UPDATE_SNAPSHOTS=1 to accept:\n{schema}"
+    );
+}
+
+#[test]
+fn route_schemas() {
+    assert_snapshot::<dvb::route::Routes>("route_routes");
+    assert_snapshot::<dvb::route::Params>("route_params");
+}
+
+#[test]
+fn point_schemas() {
+    assert_snapshot::<dvb::point::Found>("point_found");
+    assert_serialize_snapshot::<dvb::point::Found>("point_found_serialized");
+    assert_snapshot::<dvb::point::Params>("point_params");
+}
+
+#[test]
+fn trip_schemas() {
+    assert_snapshot::<dvb::trip::Trip>("trip_trip");
+}
+
+#[test]
+fn params_schemas() {
+    assert_snapshot::<dvb::monitor::Params>("monitor_params");
+    assert_snapshot::<dvb::trip::Params>("trip_params");
+    assert_snapshot::<dvb::route_changes::Params>("route_changes_params");
+    assert_snapshot::<dvb::route_changes::LinesParams>("route_changes_lines_params");
+}